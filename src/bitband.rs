@@ -0,0 +1,93 @@
+//! Bit-band access to the peripheral and upper SRAM regions
+//!
+//! Every bit of the first megabyte of the peripheral and SRAM_U regions is mapped to a whole word
+//! of an alias region. Writing that word changes the single bit in one bus access, so no
+//! read-modify-write is needed and an interrupt handler updating another bit of the same register
+//! cannot be overwritten.
+//!
+//! The core performs that read-modify-write with the width of the alias access, so the alias is
+//! accessed with the width of the register: a byte wide access to the alias of a byte register
+//! leaves the neighbouring registers of its word alone.
+// Reference: 3.3.1 ARM Cortex-M4 Core Configuration, Bit Banding
+
+use core::mem;
+use core::ptr;
+
+const SRAM_START: usize = 0x2000_0000;
+const SRAM_ALIAS_START: usize = 0x2200_0000;
+const PERIPHERAL_START: usize = 0x4000_0000;
+const PERIPHERAL_ALIAS_START: usize = 0x4200_0000;
+const REGION_SIZE: usize = 0x0010_0000;
+
+/// Width of a register accessed through its bit-band alias
+pub trait Register: Copy + From<bool> + PartialEq + private::Sealed {}
+
+impl Register for u8 {}
+impl Register for u16 {}
+impl Register for u32 {}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// Returns the address of the alias word of bit `bit` of the register at `address`, to be
+/// accessed with the width of the register
+pub fn alias<T: Register>(address: *const T, bit: u8) -> *mut T {
+    let address = address as usize;
+
+    if usize::from(bit) >= mem::size_of::<T>() * 8 {
+        panic!("Invalid bit-band bit: {}", bit);
+    }
+
+    let alias_start = match address {
+        _ if (PERIPHERAL_START..PERIPHERAL_START + REGION_SIZE).contains(&address) => {
+            PERIPHERAL_ALIAS_START + (address - PERIPHERAL_START) * 32
+        },
+        _ if (SRAM_START..SRAM_START + REGION_SIZE).contains(&address) => {
+            SRAM_ALIAS_START + (address - SRAM_START) * 32
+        },
+        _ => panic!("Address is not in a bit-band region: {:#x}", address),
+    };
+
+    (alias_start + usize::from(bit) * 4) as *mut T
+}
+
+/// Reads bit `bit` of the register at `address`
+///
+/// # Safety
+///
+/// `address` must point to a readable register.
+pub unsafe fn read<T: Register>(address: *const T, bit: u8) -> bool {
+    ptr::read_volatile(alias(address, bit)) != T::from(false)
+}
+
+/// Writes bit `bit` of the register at `address`, leaving the other bits untouched
+///
+/// # Safety
+///
+/// `address` must point to a writable register, and the caller must own bit `bit` of it.
+pub unsafe fn write<T: Register>(address: *const T, bit: u8, value: bool) {
+    ptr::write_volatile(alias(address, bit), T::from(value));
+}
+
+/// Sets bit `bit` of the register at `address`, leaving the other bits untouched
+///
+/// # Safety
+///
+/// See `write`.
+pub unsafe fn set<T: Register>(address: *const T, bit: u8) {
+    write(address, bit, true);
+}
+
+/// Clears bit `bit` of the register at `address`, leaving the other bits untouched
+///
+/// # Safety
+///
+/// See `write`.
+pub unsafe fn clear<T: Register>(address: *const T, bit: u8) {
+    write(address, bit, false);
+}
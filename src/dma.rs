@@ -0,0 +1,392 @@
+//! Enhanced Direct Memory Access (eDMA) controller and its request multiplexer (DMAMUX)
+//!
+//! Each channel moves data between memory and a peripheral without the core. The peripheral
+//! requesting the transfers is selected through the DMAMUX.
+// Reference: 21 Enhanced Direct Memory Access (eDMA)
+// Reference: 20 Direct Memory Access Multiplexer (DMAMUX)
+
+use core::sync::atomic::{self, Ordering};
+
+use mk20d7::{self, sim::{SCGC6, SCGC7}, DMA, DMAMUX};
+
+use gpio::private::Sealed;
+
+/// Largest number of transfers of a major loop
+pub const COUNT_MAX: usize = 0x7FFF;
+
+/// Extension trait to split the DMA peripherals into independent channels
+pub trait DmaExt {
+    /// The channels to split the DMA into
+    type Channels;
+
+    /// Enables the clocks of the DMA and DMAMUX and splits them into independent channels
+    fn split(self, scgc6: &SCGC6, scgc7: &SCGC7) -> Self::Channels;
+}
+
+/// DMA channel, implemented by the channels of `Channels` only
+pub trait Channel: Sealed {
+    /// Number of the channel
+    const INDEX: u8;
+
+    /// Sets the source address, its increment after each byte and its adjustment after the major
+    /// loop
+    ///
+    /// # Safety
+    ///
+    /// The DMA reads from `address` once the requests are enabled: it has to stay valid for the
+    /// whole transfer.
+    unsafe fn set_source(&mut self, address: u32, offset: i16, last_adjustment: i32);
+
+    /// Sets the destination address, its increment after each byte and its adjustment after the
+    /// major loop
+    ///
+    /// # Safety
+    ///
+    /// The DMA writes to `address` once the requests are enabled: it has to stay valid, and not
+    /// be accessed otherwise, for the whole transfer.
+    unsafe fn set_destination(&mut self, address: u32, offset: i16, last_adjustment: i32);
+
+    /// Sets the number of bytes of the major loop, moved one per request
+    fn set_count(&mut self, count: u16);
+
+    /// Makes the hardware stop the requests at the end of the major loop
+    fn set_disable_request(&mut self, disable: bool);
+
+    /// Routes the requests of a DMAMUX source to the channel
+    fn set_request_source(&mut self, source: u8);
+
+    /// Returns the number of bytes left in the current major loop
+    ///
+    /// The count is reloaded with the length of the major loop when it completes, so it does
+    /// not drop to 0 at the end of a transfer.
+    fn remaining(&self) -> u16;
+
+    /// Returns `true` once the major loop has completed
+    fn is_done(&self) -> bool;
+
+    /// Accepts the hardware requests
+    ///
+    /// # Safety
+    ///
+    /// The source and destination of the channel have to be valid for the whole transfer, see
+    /// `set_source` and `set_destination`.
+    unsafe fn enable_request(&mut self) {
+        // Reference: 21.3.8 Set Enable Request Register (DMA_SERQ)
+        dma().serq.write(|w| w.serq().bits(Self::INDEX));
+    }
+
+    /// Ignores the hardware requests
+    fn disable_request(&mut self) {
+        // Reference: 21.3.7 Clear Enable Request Register (DMA_CERQ)
+        dma().cerq.write(|w| unsafe { w.cerq().bits(Self::INDEX) });
+    }
+
+    /// Clears the done flag of the channel
+    fn clear_done(&mut self) {
+        // Reference: 21.3.9 Clear DONE Status Bit Register (DMA_CDNE)
+        dma().cdne.write(|w| unsafe { w.cdne().bits(Self::INDEX) });
+    }
+}
+
+/// Peripheral half taking part in a DMA transfer
+pub trait Payload {
+    /// Stops the DMA requests of the peripheral
+    fn stop_dma(&mut self);
+}
+
+/// DMA transfer of a whole buffer
+pub struct Transfer<CHANNEL, BUFFER, PAYLOAD> {
+    channel: CHANNEL,
+    buffer: BUFFER,
+    payload: PAYLOAD,
+}
+
+impl<CHANNEL, BUFFER, PAYLOAD> Transfer<CHANNEL, BUFFER, PAYLOAD>
+where
+    CHANNEL: Channel,
+    PAYLOAD: Payload,
+{
+    /// Starts a transfer, the channel being configured already
+    pub(crate) fn start(mut channel: CHANNEL, buffer: BUFFER, payload: PAYLOAD) -> Self {
+        atomic::compiler_fence(Ordering::Release);
+        channel.clear_done();
+        // The callers point the channel at the buffer and the peripheral, both owned by the transfer
+        unsafe { channel.enable_request() };
+
+        Transfer { channel, buffer, payload }
+    }
+
+    /// Returns `true` once the whole buffer has been transferred
+    pub fn is_done(&self) -> bool {
+        self.channel.is_done()
+    }
+
+    /// Returns the number of bytes not transferred yet
+    pub fn remaining(&self) -> usize {
+        // The hardware reloads the count at the end of the major loop
+        if self.is_done() {
+            0
+        } else {
+            usize::from(self.channel.remaining())
+        }
+    }
+
+    /// Waits for the end of the transfer and releases its resources
+    pub fn wait(mut self) -> (BUFFER, CHANNEL, PAYLOAD) {
+        while !self.is_done() {}
+
+        atomic::compiler_fence(Ordering::Acquire);
+        self.payload.stop_dma();
+        self.channel.disable_request();
+        self.channel.clear_done();
+
+        (self.buffer, self.channel, self.payload)
+    }
+}
+
+/// DMA transfer filling a buffer over and over, for continuous reception
+pub struct CircularTransfer<CHANNEL, PAYLOAD> {
+    channel: CHANNEL,
+    buffer: &'static mut [u8],
+    payload: PAYLOAD,
+    read_index: usize,
+}
+
+impl<CHANNEL, PAYLOAD> CircularTransfer<CHANNEL, PAYLOAD>
+where
+    CHANNEL: Channel,
+    PAYLOAD: Payload,
+{
+    /// Starts a transfer, the channel being configured already
+    pub(crate) fn start(mut channel: CHANNEL, buffer: &'static mut [u8], payload: PAYLOAD) -> Self {
+        atomic::compiler_fence(Ordering::Release);
+        channel.clear_done();
+        // The callers point the channel at the buffer and the peripheral, both owned by the transfer
+        unsafe { channel.enable_request() };
+
+        CircularTransfer { channel, buffer, payload, read_index: 0 }
+    }
+
+    /// Copies the bytes received since the previous call into `data`, returning how many were
+    /// copied
+    ///
+    /// Data is lost if the DMA goes around the buffer between two calls.
+    pub fn read(&mut self, data: &mut [u8]) -> usize {
+        let length = self.buffer.len();
+        let write_index = (length - usize::from(self.channel.remaining())) % length;
+        atomic::compiler_fence(Ordering::Acquire);
+
+        let mut count = 0;
+        while self.read_index != write_index && count < data.len() {
+            data[count] = unsafe { (&self.buffer[self.read_index] as *const u8).read_volatile() };
+            self.read_index = (self.read_index + 1) % length;
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Stops the transfer and releases its resources
+    pub fn stop(mut self) -> (&'static mut [u8], CHANNEL, PAYLOAD) {
+        self.payload.stop_dma();
+        self.channel.disable_request();
+        self.channel.clear_done();
+        atomic::compiler_fence(Ordering::Acquire);
+
+        (self.buffer, self.channel, self.payload)
+    }
+}
+
+fn dma<'a>() -> &'a mk20d7::dma::RegisterBlock {
+    unsafe { &(*DMA::ptr()) }
+}
+
+fn dmamux<'a>() -> &'a mk20d7::dmamux::RegisterBlock {
+    unsafe { &(*DMAMUX::ptr()) }
+}
+
+/// DMA channels
+pub struct Channels {
+    /// Channel 0
+    pub ch0: C0,
+
+    /// Channel 1
+    pub ch1: C1,
+
+    /// Channel 2
+    pub ch2: C2,
+
+    /// Channel 3
+    pub ch3: C3,
+
+    /// Channel 4
+    pub ch4: C4,
+
+    /// Channel 5
+    pub ch5: C5,
+
+    /// Channel 6
+    pub ch6: C6,
+
+    /// Channel 7
+    pub ch7: C7,
+
+    /// Channel 8
+    pub ch8: C8,
+
+    /// Channel 9
+    pub ch9: C9,
+
+    /// Channel 10
+    pub ch10: C10,
+
+    /// Channel 11
+    pub ch11: C11,
+
+    /// Channel 12
+    pub ch12: C12,
+
+    /// Channel 13
+    pub ch13: C13,
+
+    /// Channel 14
+    pub ch14: C14,
+
+    /// Channel 15
+    pub ch15: C15,
+}
+
+impl DmaExt for (DMA, DMAMUX) {
+    type Channels = Channels;
+
+    fn split(self, scgc6: &SCGC6, scgc7: &SCGC7) -> Channels {
+        // Reference: 12.2.10 System Clock Gating Control Register 6 (SIM_SCGC6)
+        scgc6.modify(|_, w| w.dmamux().set_bit());
+
+        // Reference: 12.2.11 System Clock Gating Control Register 7 (SIM_SCGC7)
+        scgc7.modify(|_, w| w.dma().set_bit());
+
+        Channels {
+            ch0: C0 { _0: () },
+            ch1: C1 { _0: () },
+            ch2: C2 { _0: () },
+            ch3: C3 { _0: () },
+            ch4: C4 { _0: () },
+            ch5: C5 { _0: () },
+            ch6: C6 { _0: () },
+            ch7: C7 { _0: () },
+            ch8: C8 { _0: () },
+            ch9: C9 { _0: () },
+            ch10: C10 { _0: () },
+            ch11: C11 { _0: () },
+            ch12: C12 { _0: () },
+            ch13: C13 { _0: () },
+            ch14: C14 { _0: () },
+            ch15: C15 { _0: () },
+        }
+    }
+}
+
+macro_rules! channels {
+    ($(
+        $CX:ident: ($chx:ident, $x:expr, $saddr:ident, $soff:ident, $attr:ident, $nbytes:ident,
+            $slast:ident, $daddr:ident, $doff:ident, $citer:ident, $dlastsga:ident, $csr:ident,
+            $biter:ident),
+    )+) => {
+        $(
+            /// DMA channel
+            pub struct $CX {
+                _0: (),
+            }
+
+            impl Sealed for $CX {}
+
+            // Reference: 21.3 Memory map/register definition, TCD registers
+            impl Channel for $CX {
+                const INDEX: u8 = $x;
+
+                unsafe fn set_source(&mut self, address: u32, offset: i16, last_adjustment: i32) {
+                    let dma = dma();
+                    dma.$saddr.write(|w| w.bits(address));
+                    dma.$soff.write(|w| w.bits(offset as u16));
+                    dma.$slast.write(|w| w.bits(last_adjustment as u32));
+                }
+
+                unsafe fn set_destination(&mut self, address: u32, offset: i16, last_adjustment: i32) {
+                    let dma = dma();
+                    dma.$daddr.write(|w| w.bits(address));
+                    dma.$doff.write(|w| w.bits(offset as u16));
+                    dma.$dlastsga.write(|w| w.bits(last_adjustment as u32));
+                }
+
+                fn set_count(&mut self, count: u16) {
+                    if count == 0 || usize::from(count) > COUNT_MAX {
+                        panic!("Invalid DMA count: {}", count);
+                    }
+
+                    let dma = dma();
+
+                    // Byte transfers, one byte per request
+                    dma.$attr.write(|w| unsafe { w.bits(0) });
+                    dma.$nbytes.write(|w| unsafe { w.bits(1) });
+                    dma.$citer.write(|w| unsafe { w.bits(count) });
+                    dma.$biter.write(|w| unsafe { w.bits(count) });
+                }
+
+                fn set_disable_request(&mut self, disable: bool) {
+                    dma().$csr.write(|w| w.dreq().bit(disable));
+                }
+
+                fn set_request_source(&mut self, source: u8) {
+                    // Reference: 20.3.1 Channel Configuration register (DMAMUX_CHCFGn)
+                    let chcfg = &dmamux().chcfg[usize::from($x as u8)];
+                    chcfg.write(|w| unsafe { w.bits(0) });
+                    chcfg.write(|w| unsafe { w.source().bits(source) }.enbl().set_bit());
+                }
+
+                fn remaining(&self) -> u16 {
+                    dma().$citer.read().citer().bits()
+                }
+
+                fn is_done(&self) -> bool {
+                    dma().$csr.read().done().bit_is_set()
+                }
+            }
+        )+
+    }
+}
+
+channels! {
+    C0: (ch0, 0, tcd0_saddr, tcd0_soff, tcd0_attr, tcd0_nbytes_mlno, tcd0_slast, tcd0_daddr,
+        tcd0_doff, tcd0_citer_elinkno, tcd0_dlastsga, tcd0_csr, tcd0_biter_elinkno),
+    C1: (ch1, 1, tcd1_saddr, tcd1_soff, tcd1_attr, tcd1_nbytes_mlno, tcd1_slast, tcd1_daddr,
+        tcd1_doff, tcd1_citer_elinkno, tcd1_dlastsga, tcd1_csr, tcd1_biter_elinkno),
+    C2: (ch2, 2, tcd2_saddr, tcd2_soff, tcd2_attr, tcd2_nbytes_mlno, tcd2_slast, tcd2_daddr,
+        tcd2_doff, tcd2_citer_elinkno, tcd2_dlastsga, tcd2_csr, tcd2_biter_elinkno),
+    C3: (ch3, 3, tcd3_saddr, tcd3_soff, tcd3_attr, tcd3_nbytes_mlno, tcd3_slast, tcd3_daddr,
+        tcd3_doff, tcd3_citer_elinkno, tcd3_dlastsga, tcd3_csr, tcd3_biter_elinkno),
+    C4: (ch4, 4, tcd4_saddr, tcd4_soff, tcd4_attr, tcd4_nbytes_mlno, tcd4_slast, tcd4_daddr,
+        tcd4_doff, tcd4_citer_elinkno, tcd4_dlastsga, tcd4_csr, tcd4_biter_elinkno),
+    C5: (ch5, 5, tcd5_saddr, tcd5_soff, tcd5_attr, tcd5_nbytes_mlno, tcd5_slast, tcd5_daddr,
+        tcd5_doff, tcd5_citer_elinkno, tcd5_dlastsga, tcd5_csr, tcd5_biter_elinkno),
+    C6: (ch6, 6, tcd6_saddr, tcd6_soff, tcd6_attr, tcd6_nbytes_mlno, tcd6_slast, tcd6_daddr,
+        tcd6_doff, tcd6_citer_elinkno, tcd6_dlastsga, tcd6_csr, tcd6_biter_elinkno),
+    C7: (ch7, 7, tcd7_saddr, tcd7_soff, tcd7_attr, tcd7_nbytes_mlno, tcd7_slast, tcd7_daddr,
+        tcd7_doff, tcd7_citer_elinkno, tcd7_dlastsga, tcd7_csr, tcd7_biter_elinkno),
+    C8: (ch8, 8, tcd8_saddr, tcd8_soff, tcd8_attr, tcd8_nbytes_mlno, tcd8_slast, tcd8_daddr,
+        tcd8_doff, tcd8_citer_elinkno, tcd8_dlastsga, tcd8_csr, tcd8_biter_elinkno),
+    C9: (ch9, 9, tcd9_saddr, tcd9_soff, tcd9_attr, tcd9_nbytes_mlno, tcd9_slast, tcd9_daddr,
+        tcd9_doff, tcd9_citer_elinkno, tcd9_dlastsga, tcd9_csr, tcd9_biter_elinkno),
+    C10: (ch10, 10, tcd10_saddr, tcd10_soff, tcd10_attr, tcd10_nbytes_mlno, tcd10_slast, tcd10_daddr,
+        tcd10_doff, tcd10_citer_elinkno, tcd10_dlastsga, tcd10_csr, tcd10_biter_elinkno),
+    C11: (ch11, 11, tcd11_saddr, tcd11_soff, tcd11_attr, tcd11_nbytes_mlno, tcd11_slast, tcd11_daddr,
+        tcd11_doff, tcd11_citer_elinkno, tcd11_dlastsga, tcd11_csr, tcd11_biter_elinkno),
+    C12: (ch12, 12, tcd12_saddr, tcd12_soff, tcd12_attr, tcd12_nbytes_mlno, tcd12_slast, tcd12_daddr,
+        tcd12_doff, tcd12_citer_elinkno, tcd12_dlastsga, tcd12_csr, tcd12_biter_elinkno),
+    C13: (ch13, 13, tcd13_saddr, tcd13_soff, tcd13_attr, tcd13_nbytes_mlno, tcd13_slast, tcd13_daddr,
+        tcd13_doff, tcd13_citer_elinkno, tcd13_dlastsga, tcd13_csr, tcd13_biter_elinkno),
+    C14: (ch14, 14, tcd14_saddr, tcd14_soff, tcd14_attr, tcd14_nbytes_mlno, tcd14_slast, tcd14_daddr,
+        tcd14_doff, tcd14_citer_elinkno, tcd14_dlastsga, tcd14_csr, tcd14_biter_elinkno),
+    C15: (ch15, 15, tcd15_saddr, tcd15_soff, tcd15_attr, tcd15_nbytes_mlno, tcd15_slast, tcd15_daddr,
+        tcd15_doff, tcd15_citer_elinkno, tcd15_dlastsga, tcd15_csr, tcd15_biter_elinkno),
+}
@@ -1,5 +1,15 @@
 use core::marker::PhantomData;
-use mk20d7::sim::SCGC5;
+
+use bit_field::BitField;
+use hal::digital::{InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin};
+use mk20d7::{sim::SCGC5, pta, PTA, PTB, PTC, PTD, PTE};
+
+pub mod signals;
+
+pub(crate) mod private {
+    /// Implemented by the types of this crate only
+    pub trait Sealed {}
+}
 
 /// Extension trait to split a GPIO peripheral in independent pins and registers
 pub trait GpioExt {
@@ -24,6 +34,46 @@ pub struct PullDown;
 /// Pulled up input (type state)
 pub struct PullUp;
 
+/// Dynamic mode (type state)
+///
+/// The direction and pull resistor of a dynamic pin can be changed at runtime, without the port
+/// registers.
+pub struct Dynamic;
+
+/// Dynamic pin error
+#[derive(Debug)]
+pub enum DynamicError {
+    /// The pin is an input, but an output operation was requested
+    NotOutput,
+
+    /// The pin is an output, but an input operation was requested
+    NotInput,
+}
+
+/// Digitally filtered input (type state)
+pub struct Filtered<MODE> {
+    _mode: PhantomData<MODE>,
+}
+
+/// Input modes which can be passed through the port's digital filter
+pub trait Filterable {}
+
+impl Filterable for Floating {}
+impl Filterable for PullDown {}
+impl Filterable for PullUp {}
+
+/// Clock source of the digital input filters of a port
+pub enum FilterClock {
+    /// Digital filters are clocked by the bus clock
+    BusClock,
+
+    /// Digital filters are clocked by the 1 kHz low power oscillator
+    Lpo,
+}
+
+/// Maximum width of a digital input filter, in filter clock cycles
+pub const FILTER_WIDTH_MAX: u8 = 31;
+
 /// Output mode (type state)
 pub struct Output<MODE> {
     _mode: PhantomData<MODE>,
@@ -35,6 +85,200 @@ pub struct PushPull;
 /// Open drain output (type state)
 pub struct OpenDrain;
 
+/// Pin whose Pin Control Register is locked until the next reset
+///
+/// A locked pin keeps the configuration it had when it was locked and can no longer be converted
+/// into another mode.
+pub struct Locked<PIN> {
+    pin: PIN,
+}
+
+impl<PIN: OutputPin> OutputPin for Locked<PIN> {
+    fn set_high(&mut self) {
+        self.pin.set_high()
+    }
+
+    fn set_low(&mut self) {
+        self.pin.set_low()
+    }
+}
+
+impl<PIN: StatefulOutputPin> StatefulOutputPin for Locked<PIN> {
+    fn is_set_high(&self) -> bool {
+        self.pin.is_set_high()
+    }
+
+    fn is_set_low(&self) -> bool {
+        self.pin.is_set_low()
+    }
+}
+
+impl<PIN: ToggleableOutputPin> ToggleableOutputPin for Locked<PIN> {
+    fn toggle(&mut self) {
+        self.pin.toggle()
+    }
+}
+
+impl<PIN: InputPin> InputPin for Locked<PIN> {
+    fn is_high(&self) -> bool {
+        self.pin.is_high()
+    }
+
+    fn is_low(&self) -> bool {
+        self.pin.is_low()
+    }
+}
+
+/// Pins of a single port which can be accessed together through a port `Group`
+pub trait PortPins<PORT> {
+    /// Mask of the bits owned by these pins in the port registers
+    fn mask(&self) -> u32;
+}
+
+/// Pins of a single port which are all outputs
+pub trait OutputPortPins<PORT>: PortPins<PORT> {}
+
+/// Pins of a single port which are all inputs
+pub trait InputPortPins<PORT>: PortPins<PORT> {}
+
+impl<PORT, PIN: PortPins<PORT>> PortPins<PORT> for Locked<PIN> {
+    fn mask(&self) -> u32 {
+        self.pin.mask()
+    }
+}
+
+impl<PORT, PIN: OutputPortPins<PORT>> OutputPortPins<PORT> for Locked<PIN> {}
+impl<PORT, PIN: InputPortPins<PORT>> InputPortPins<PORT> for Locked<PIN> {}
+
+impl<PORT, PIN: PortPins<PORT>, const N: usize> PortPins<PORT> for [PIN; N] {
+    fn mask(&self) -> u32 {
+        self.iter().fold(0, |mask, pin| mask | pin.mask())
+    }
+}
+
+impl<PORT, PIN: OutputPortPins<PORT>, const N: usize> OutputPortPins<PORT> for [PIN; N] {}
+impl<PORT, PIN: InputPortPins<PORT>, const N: usize> InputPortPins<PORT> for [PIN; N] {}
+
+macro_rules! port_pins {
+    ($(($($PIN:ident: $n:tt),+),)+) => {
+        $(
+            impl<PORT, $($PIN: PortPins<PORT>),+> PortPins<PORT> for ($($PIN,)+) {
+                fn mask(&self) -> u32 {
+                    0 $(| self.$n.mask())+
+                }
+            }
+
+            impl<PORT, $($PIN: OutputPortPins<PORT>),+> OutputPortPins<PORT> for ($($PIN,)+) {}
+            impl<PORT, $($PIN: InputPortPins<PORT>),+> InputPortPins<PORT> for ($($PIN,)+) {}
+        )+
+    }
+}
+
+port_pins! {
+    (P0: 0),
+    (P0: 0, P1: 1),
+    (P0: 0, P1: 1, P2: 2),
+    (P0: 0, P1: 1, P2: 2, P3: 3),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4, P5: 5),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4, P5: 5, P6: 6),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4, P5: 5, P6: 6, P7: 7),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4, P5: 5, P6: 6, P7: 7, P8: 8),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4, P5: 5, P6: 6, P7: 7, P8: 8, P9: 9),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4, P5: 5, P6: 6, P7: 7, P8: 8, P9: 9, P10: 10),
+    (P0: 0, P1: 1, P2: 2, P3: 3, P4: 4, P5: 5, P6: 6, P7: 7, P8: 8, P9: 9, P10: 10, P11: 11),
+}
+
+/// Port of a fully erased pin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Port {
+    /// General Purpose Input/Output Port A
+    A,
+
+    /// General Purpose Input/Output Port B
+    B,
+
+    /// General Purpose Input/Output Port C
+    C,
+
+    /// General Purpose Input/Output Port D
+    D,
+
+    /// General Purpose Input/Output Port E
+    E,
+}
+
+/// Fully erased pin
+///
+/// This is useful when you want to collect pins of different ports into an array.
+pub struct Pin<MODE> {
+    port: Port,
+    i: u8,
+    _mode: PhantomData<MODE>,
+}
+
+impl<MODE> Pin<MODE> {
+    /// Returns the port of this pin
+    pub fn port(&self) -> Port {
+        self.port
+    }
+
+    /// Returns the number of this pin within its port
+    pub fn pin(&self) -> u8 {
+        self.i
+    }
+
+    fn ptx(&self) -> &pta::RegisterBlock {
+        // Every GPIO port shares the register block layout of port A
+        // Reference: 49.2 Memory map and register definition
+        unsafe {
+            match self.port {
+                Port::A => &*PTA::ptr(),
+                Port::B => &*(PTB::ptr() as *const pta::RegisterBlock),
+                Port::C => &*(PTC::ptr() as *const pta::RegisterBlock),
+                Port::D => &*(PTD::ptr() as *const pta::RegisterBlock),
+                Port::E => &*(PTE::ptr() as *const pta::RegisterBlock),
+            }
+        }
+    }
+}
+
+impl<MODE> StatefulOutputPin for Pin<Output<MODE>> {
+    fn is_set_high(&self) -> bool {
+        self.ptx().pdor.read().bits() & (1 << self.i) != 0
+    }
+
+    fn is_set_low(&self) -> bool {
+        !self.is_set_high()
+    }
+}
+
+impl<MODE> OutputPin for Pin<Output<MODE>> {
+    fn set_high(&mut self) {
+        self.ptx().psor.write(|w| unsafe { w.bits(1 << self.i) })
+    }
+
+    fn set_low(&mut self) {
+        self.ptx().pcor.write(|w| unsafe { w.bits(1 << self.i) })
+    }
+}
+
+impl<MODE> ToggleableOutputPin for Pin<Output<MODE>> {
+    fn toggle(&mut self) {
+        self.ptx().ptor.write(|w| unsafe { w.bits(1 << self.i) })
+    }
+}
+
+impl<MODE> InputPin for Pin<Input<MODE>> {
+    fn is_high(&self) -> bool {
+        !self.is_low()
+    }
+
+    fn is_low(&self) -> bool {
+        self.ptx().pdir.read().bits() & (1 << self.i) == 0
+    }
+}
+
 pub struct Alternate<MODE> {
     _mode: PhantomData<MODE>,
 }
@@ -63,28 +307,167 @@ pub struct ALT6;
 /// Alternate function 7 (type state, chip specific / JTAG / NMI)
 pub struct ALT7;
 
-// Pin mux controller mode
-enum PinMux {
+/// Pins which have the alternate function `ALT`
+///
+/// ALT0 (analog / disabled) and ALT1 (GPIO) are available on every pin.
+pub trait HasAlternate<ALT>: private::Sealed {}
+
+/// Pins whose input level can be read whatever their mode, e.g. while a peripheral drives them
+pub trait PinLevel: private::Sealed {
+    /// Returns `true` if the pin is driven high
+    fn is_level_high() -> bool;
+}
+
+/// Pin mux controller mode
+#[derive(Clone, Copy)]
+pub enum PinMux {
+    /// Alternate function 0 (Analog / disabled)
     ALT0,
+
+    /// Alternate function 1 (GPIO)
     ALT1,
+
+    /// Alternate function 2 (chip specific)
     ALT2,
+
+    /// Alternate function 3 (chip specific)
     ALT3,
+
+    /// Alternate function 4 (chip specific)
     ALT4,
+
+    /// Alternate function 5 (chip specific)
     ALT5,
+
+    /// Alternate function 6 (chip specific)
     ALT6,
+
+    /// Alternate function 7 (chip specific / JTAG / NMI)
     ALT7,
 }
 
+// Pin mux of an alternate function (type state)
+trait AlternateFunction {
+    const MUX: PinMux;
+}
+
+impl AlternateFunction for Alternate<ALT0> { const MUX: PinMux = PinMux::ALT0; }
+impl AlternateFunction for Alternate<ALT1> { const MUX: PinMux = PinMux::ALT1; }
+impl AlternateFunction for Alternate<ALT2> { const MUX: PinMux = PinMux::ALT2; }
+impl AlternateFunction for Alternate<ALT3> { const MUX: PinMux = PinMux::ALT3; }
+impl AlternateFunction for Alternate<ALT4> { const MUX: PinMux = PinMux::ALT4; }
+impl AlternateFunction for Alternate<ALT5> { const MUX: PinMux = PinMux::ALT5; }
+impl AlternateFunction for Alternate<ALT6> { const MUX: PinMux = PinMux::ALT6; }
+impl AlternateFunction for Alternate<ALT7> { const MUX: PinMux = PinMux::ALT7; }
+
 // Pin mode (when pin is in ALT1 gpio mode)
 enum PinMode {
     Output,
-
-    #[allow(dead_code)]
     Input,
 }
 
+/// Pin pull resistor
+#[derive(Clone, Copy)]
+pub enum PinPull {
+    /// No pull resistor
+    Disabled,
+
+    /// Internal pull down resistor
+    Down,
+
+    /// Internal pull up resistor
+    Up,
+}
+
+/// Pin output drive strength
+#[derive(Clone, Copy)]
+pub enum DriveStrength {
+    /// Low drive strength
+    Low,
+
+    /// High drive strength
+    High,
+}
+
+/// Pin output slew rate
+#[derive(Clone, Copy)]
+pub enum SlewRate {
+    /// Fast slew rate
+    Fast,
+
+    /// Slow slew rate
+    Slow,
+}
+
+/// Pin Control Register configuration, applied to many pins at once through the `GPCLR` and
+/// `GPCHR` registers of a port
+#[derive(Clone, Copy)]
+pub struct PinConfig {
+    /// Pin mux controller mode
+    pub mux: PinMux,
+
+    /// Pull resistor
+    pub pull: PinPull,
+
+    /// Output drive strength
+    pub drive_strength: DriveStrength,
+
+    /// Output slew rate
+    pub slew_rate: SlewRate,
+
+    /// Open drain output
+    pub open_drain: bool,
+
+    /// Passive input filter
+    pub passive_filter: bool,
+}
+
+impl Default for PinConfig {
+    fn default() -> Self {
+        PinConfig {
+            mux: PinMux::ALT0,
+            pull: PinPull::Disabled,
+            drive_strength: DriveStrength::Low,
+            slew_rate: SlewRate::Fast,
+            open_drain: false,
+            passive_filter: false,
+        }
+    }
+}
+
+impl PinConfig {
+    // Lower half of the Pin Control Register, as written through GPWD
+    // Reference: 11.14.1 Pin Control Register n (PORTx_PCRn)
+    fn bits(&self) -> u16 {
+        let mut bits = 0;
+        bits.set_bits(8..11, self.mux as u16);
+        match self.pull {
+            PinPull::Disabled => {},
+            PinPull::Down => { bits.set_bit(1, true); },
+            PinPull::Up => { bits.set_bit(1, true).set_bit(0, true); },
+        }
+        bits.set_bit(2, match self.slew_rate { SlewRate::Fast => false, SlewRate::Slow => true });
+        bits.set_bit(4, self.passive_filter);
+        bits.set_bit(5, self.open_drain);
+        bits.set_bit(6, match self.drive_strength { DriveStrength::Low => false, DriveStrength::High => true });
+        bits
+    }
+}
+
+// Reset value of the Pin Control Register: the JTAG / SWD and NMI pins start with their pull
+// resistor enabled, every other pin with its pull disabled
+// Reference: 11.14.1 Pin Control Register n (PORTx_PCRn)
+fn reset_pcr(port: Port, pin: u8, mux: PinMux) -> u32 {
+    let pull = match (port, pin) {
+        (Port::A, 0) => PinPull::Down,
+        (Port::A, 1..=4) => PinPull::Up,
+        _ => PinPull::Disabled,
+    };
+    u32::from(PinConfig { mux, pull, ..PinConfig::default() }.bits())
+}
+
 macro_rules! gpio {
-    ($PORTX:ident, $portx:ident, $PTX:ident, $ptx:ident, $gpiox:ident, $docport:expr, [ $($PTXi:ident: ($ptxi:ident, $i:expr, $MODE:ty, $docpin:expr),)+]) =>
+    ($PORTX:ident, $portx:ident, $PTX:ident, $ptx:ident, $gpiox:ident, $Port:ident, $docport:expr, [ $($(#[$pattr:meta])* $PTXi:ident: ($ptxi:ident, $i:expr, $MODE:ty, $docpin:expr),)+]) =>
     {
         #[doc = "General Purpose Input/Output Port "]
         #[doc = $docport]
@@ -92,21 +475,30 @@ macro_rules! gpio {
             use core::marker::PhantomData;
 
             use hal::digital::{
-                OutputPin, StatefulOutputPin, InputPin,
-                toggleable,
+                v2,
+                OutputPin, StatefulOutputPin, ToggleableOutputPin, InputPin,
             };
 
             use mk20d7::{sim::SCGC5, $PORTX, $PTX, $portx, $ptx};
 
+            use bitband;
+
+            use super::private::Sealed;
+
             use super::{
-                Floating, GpioExt, Input, Output,
-                PushPull,
+                Dynamic, DynamicError, Floating, GpioExt, Input, Output,
+                PullDown, PullUp, PushPull,
+                Filtered, Filterable, FilterClock, FILTER_WIDTH_MAX, PinConfig, Locked,
+                Pin, Port, PortPins, OutputPortPins, InputPortPins, HasAlternate,
                 Alternate, ALT0, ALT1, ALT2, ALT3, ALT4, ALT5, ALT6, ALT7,
-                PinMux, PinMode,
+                PinMux, PinMode, PinPull, PinLevel, AlternateFunction, reset_pcr,
             };
 
             /// General Purpose Input/Output and Pin Control and Interrupts parts
             pub struct Parts {
+                /// Port peripherals, given back by `Parts::free`
+                pub peripherals: Peripherals,
+
                 // Pin Control and Interrupts parts
                 /// Digital Filter Clock Register
                 pub dfcr: DFCR,
@@ -153,6 +545,7 @@ macro_rules! gpio {
                     #[doc = $docport]
                     #[doc = " Pin "]
                     #[doc = $docpin]
+                    $(#[$pattr])*
                     pub $ptxi: $PTXi<$MODE>,
                 )+
             }
@@ -163,9 +556,11 @@ macro_rules! gpio {
                 fn split(self, scgc5: &SCGC5) -> Self::Parts {
                     // Enable the GPIO module
                     // Reference: 10.2.3 Clock gating
-                    scgc5.write(|w| w.$portx().set_bit());
+                    scgc5.modify(|_, w| w.$portx().set_bit());
 
+                    let (ptx, portx) = self;
                     Parts {
+                        peripherals: Peripherals { ptx, portx },
                         dfcr: DFCR { _0: () },
                         dfer: DFER { _0: () },
                         dfwr: DFWR { _0: () },
@@ -180,22 +575,65 @@ macro_rules! gpio {
                         psor: PSOR { _0: () },
                         ptor: PTOR { _0: () },
                         $(
+                            $(#[$pattr])*
                             $ptxi: $PTXi {_mode: PhantomData},
                         )+
                     }
                 }
             }
 
+            impl Parts {
+                /// Releases the port peripherals
+                ///
+                /// Every pin has to be given back in its reset state, see `into_reset_state`.
+                pub fn free(self) -> ($PTX, $PORTX) {
+                    self.peripherals.free()
+                }
+            }
+
+            /// General Purpose Input/Output and Pin Control and Interrupts peripherals
+            pub struct Peripherals {
+                ptx: $PTX,
+                portx: $PORTX,
+            }
+
+            impl Peripherals {
+                /// Releases the port peripherals, for parts that were split further
+                pub fn free(self) -> ($PTX, $PORTX) {
+                    (self.ptx, self.portx)
+                }
+            }
+
             /// Digital Filter Clock Register
             pub struct DFCR {
                 _0: (),
             }
 
             impl DFCR {
-                #[allow(dead_code)]
                 pub(crate) fn dfcr(&mut self) -> &$portx::DFCR {
                     unsafe { &(*$PORTX::ptr()).dfcr }
                 }
+
+                /// Selects the clock source of every digital filter on this port
+                ///
+                /// The clock source should only be changed while all digital filters on the port
+                /// are disabled.
+                // Reference: 11.14.6 Digital Filter Clock Register (PORTx_DFCR)
+                pub fn set_clock(&mut self, clock: FilterClock) {
+                    self.dfcr().write(|w| match clock {
+                        FilterClock::BusClock => w.cs()._0(),
+                        FilterClock::Lpo => w.cs()._1(),
+                    });
+                }
+
+                /// Returns the clock source of the digital filters on this port
+                pub fn get_clock(&mut self) -> FilterClock {
+                    if self.dfcr().read().cs().bit_is_set() {
+                        FilterClock::Lpo
+                    } else {
+                        FilterClock::BusClock
+                    }
+                }
             }
 
             /// Digital Filter Enable Register
@@ -204,7 +642,6 @@ macro_rules! gpio {
             }
 
             impl DFER {
-                #[allow(dead_code)]
                 pub(crate) fn dfer(&mut self) -> &$portx::DFER {
                     unsafe { &(*$PORTX::ptr()).dfer }
                 }
@@ -216,10 +653,28 @@ macro_rules! gpio {
             }
 
             impl DFWR {
-                #[allow(dead_code)]
                 pub(crate) fn dfwr(&mut self) -> &$portx::DFWR {
                     unsafe { &(*$PORTX::ptr()).dfwr }
                 }
+
+                /// Sets the width of every digital filter on this port, in filter clock cycles
+                ///
+                /// Glitches shorter than `width` cycles are filtered out; a width of zero disables
+                /// filtering. The width should only be changed while all digital filters on the port
+                /// are disabled.
+                // Reference: 11.14.7 Digital Filter Width Register (PORTx_DFWR)
+                pub fn set_width(&mut self, width: u8) {
+                    if width > FILTER_WIDTH_MAX {
+                        panic!("Invalid digital filter width: {}", width);
+                    }
+
+                    self.dfwr().write(|w| unsafe { w.filt().bits(width) });
+                }
+
+                /// Returns the width of the digital filters on this port, in filter clock cycles
+                pub fn get_width(&mut self) -> u8 {
+                    self.dfwr().read().filt().bits()
+                }
             }
 
             /// Global Pin Control High Register
@@ -227,11 +682,49 @@ macro_rules! gpio {
                 _0: (),
             }
 
+            impl GPCHR {
+                pub(crate) fn gpchr(&mut self) -> &$portx::GPCHR {
+                    unsafe { &(*$PORTX::ptr()).gpchr }
+                }
+
+                /// Applies `config` to the Pin Control Register of pins 16 to 31 in a single write
+                ///
+                /// Bit `n` of `pins` selects pin `16 + n`. The type state of the affected pins is not
+                /// updated, so this is meant for board initialization code. Locked pins are left
+                /// unchanged.
+                // Reference: 11.14.3 Global Pin Control High Register (PORTx_GPCHR)
+                pub fn configure(&mut self, pins: u16, config: &PinConfig) {
+                    self.gpchr().write(|w| unsafe {
+                        w.gpwd().bits(config.bits());
+                        w.gpwe().bits(pins)
+                    });
+                }
+            }
+
             /// Global Pin Control Low Register
             pub struct GPCLR {
                 _0: (),
             }
 
+            impl GPCLR {
+                pub(crate) fn gpclr(&mut self) -> &$portx::GPCLR {
+                    unsafe { &(*$PORTX::ptr()).gpclr }
+                }
+
+                /// Applies `config` to the Pin Control Register of pins 0 to 15 in a single write
+                ///
+                /// Bit `n` of `pins` selects pin `n`. The type state of the affected pins is not
+                /// updated, so this is meant for board initialization code. Locked pins are left
+                /// unchanged.
+                // Reference: 11.14.2 Global Pin Control Low Register (PORTx_GPCLR)
+                pub fn configure(&mut self, pins: u16, config: &PinConfig) {
+                    self.gpclr().write(|w| unsafe {
+                        w.gpwd().bits(config.bits());
+                        w.gpwe().bits(pins)
+                    });
+                }
+            }
+
             /// Interrupt Status Flag Register
             pub struct ISFR {
                 _0: (),
@@ -261,6 +754,7 @@ macro_rules! gpio {
             }
 
             impl PCOR {
+                #[allow(dead_code)]
                 pub(crate) fn pcor(&mut self) -> &$ptx::PCOR {
                     unsafe { &(*$PTX::ptr()).pcor }
                 }
@@ -283,6 +777,7 @@ macro_rules! gpio {
             }
 
             impl PDIR {
+                #[allow(dead_code)]
                 pub(crate) fn pdir(&mut self) -> &$ptx::PDIR {
                     unsafe { &(*$PTX::ptr()).pdir }
                 }
@@ -294,6 +789,7 @@ macro_rules! gpio {
             }
 
             impl PDOR {
+                #[allow(dead_code)]
                 pub(crate) fn pdor(&mut self) -> &$ptx::PDOR {
                     unsafe { &(*$PTX::ptr()).pdor }
                 }
@@ -305,6 +801,7 @@ macro_rules! gpio {
             }
 
             impl PSOR {
+                #[allow(dead_code)]
                 pub(crate) fn psor(&mut self) -> &$ptx::PSOR {
                     unsafe { &(*$PTX::ptr()).psor }
                 }
@@ -322,6 +819,10 @@ macro_rules! gpio {
                 }
             }
 
+            fn ptx<'a>() -> &'a $ptx::RegisterBlock {
+                unsafe { &(*$PTX::ptr()) }
+            }
+
             fn set_pin_mux(pin: usize, pcr: &mut PCR, pin_mux: PinMux) {
                 let alt = match pin_mux {
                     PinMux::ALT0 => $portx::pcr::MUXW::_000,
@@ -334,21 +835,215 @@ macro_rules! gpio {
                     PinMux::ALT7 => $portx::pcr::MUXW::_111,
                 };
 
-                pcr.pcr()[pin].write(|w| w.mux().variant(alt));
+                // Only the mux changes, the pull, drive, filter and interrupt settings are kept
+                // ISF is cleared by writing 1, so a pending flag is not written back
+                pcr.pcr()[pin].modify(|_, w| w.isf().clear_bit().mux().variant(alt));
             }
 
-            fn set_pin_mode(pin: u32, pddr: &mut PDDR, pin_mode: PinMode) {
-                let bit = match pin_mode {
-                    PinMode::Output => 1 << pin,
-                    PinMode::Input => !(1 << pin),
+            fn set_pin_mode(pin: u8, pddr: &mut PDDR, pin_mode: PinMode) {
+                let output = match pin_mode {
+                    PinMode::Output => true,
+                    PinMode::Input => false,
                 };
-                pddr.pddr().modify(|r, w| unsafe { w.bits(r.bits() | bit) });
+                // PDDR is 32 bits wide
+                let pddr = pddr.pddr() as *const _ as *const u32;
+                unsafe { bitband::write(pddr, pin, output) };
+            }
+
+            fn set_pin_pull(pin: usize, pcr: &mut PCR, pin_pull: PinPull) {
+                // ISF is cleared by writing 1, so a pending flag is not written back
+                // Reference: 11.14.1 Pin Control Register n (PORTx_PCRn)
+                pcr.pcr()[pin].modify(|_, w| {
+                    let w = w.isf().clear_bit();
+                    match pin_pull {
+                        PinPull::Disabled => w.pe().clear_bit(),
+                        PinPull::Down => w.pe().set_bit().ps().clear_bit(),
+                        PinPull::Up => w.pe().set_bit().ps().set_bit(),
+                    }
+                });
+            }
+
+            // Dynamic pins own their bit of the PDDR, which is updated through its bit-band alias
+            fn set_dynamic_pin_mode(pin: u8, pin_mode: PinMode) {
+                set_pin_mode(pin, &mut PDDR { _0: () }, pin_mode);
+            }
+
+            // Dynamic pins own their PCR
+            fn set_dynamic_pin_pull(pin: usize, pin_pull: PinPull) {
+                set_pin_pull(pin, &mut PCR { _0: () }, pin_pull);
+            }
+
+            fn is_dynamic_pin_output(pin: u8) -> bool {
+                ptx().pddr.read().bits() & (1 << pin) != 0
+            }
+
+            // Only `Input<Filtered<_>>` pins are filtered, so the mode conversions clear the bit of
+            // the pin in the DFER; each pin owns its bit, which is updated through its bit-band alias
+            fn clear_pin_digital_filter(pin: u8) {
+                set_digital_filter(pin, &mut DFER { _0: () }, false);
+            }
+
+            // Reference: 11.14.5 Digital Filter Enable Register (PORTx_DFER)
+            fn set_digital_filter(pin: u8, dfer: &mut DFER, enabled: bool) {
+                // DFER is 32 bits wide
+                let dfer = dfer.dfer() as *const _ as *const u32;
+                unsafe { bitband::write(dfer, pin, enabled) };
+            }
+
+            /// Partially erased pin of General Purpose Input/Output Port
+            #[doc = $docport]
+            pub struct PTx<MODE> {
+                i: u8,
+                _mode: PhantomData<MODE>,
+            }
+
+            impl<MODE> PTx<MODE> {
+                /// Erases the port number from the type
+                pub fn downgrade(self) -> Pin<MODE> {
+                    Pin { port: Port::$Port, i: self.i, _mode: PhantomData }
+                }
+
+                /// Returns the number of this pin within its port
+                pub fn pin(&self) -> u8 {
+                    self.i
+                }
+            }
+
+            impl<MODE> StatefulOutputPin for PTx<Output<MODE>> {
+                fn is_set_high(&self) -> bool {
+                    ptx().pdor.read().bits() & (1 << self.i) != 0
+                }
+
+                fn is_set_low(&self) -> bool {
+                    !self.is_set_high()
+                }
+            }
+
+            impl<MODE> OutputPin for PTx<Output<MODE>> {
+                fn set_high(&mut self) {
+                    ptx().psor.write(|w| unsafe { w.bits(1 << self.i) })
+                }
+
+                fn set_low(&mut self) {
+                    ptx().pcor.write(|w| unsafe { w.bits(1 << self.i) })
+                }
+            }
+
+            impl<MODE> ToggleableOutputPin for PTx<Output<MODE>> {
+                fn toggle(&mut self) {
+                    ptx().ptor.write(|w| unsafe { w.bits(1 << self.i) })
+                }
+            }
+
+            impl<MODE> InputPin for PTx<Input<MODE>> {
+                fn is_high(&self) -> bool {
+                    !self.is_low()
+                }
+
+                fn is_low(&self) -> bool {
+                    ptx().pdir.read().bits() & (1 << self.i) == 0
+                }
+            }
+
+            impl<MODE> PortPins<$PTX> for PTx<MODE> {
+                fn mask(&self) -> u32 {
+                    1 << self.i
+                }
+            }
+
+            impl<MODE> OutputPortPins<$PTX> for PTx<Output<MODE>> {}
+            impl<MODE> InputPortPins<$PTX> for PTx<Input<MODE>> {}
+
+            /// Group of pins of General Purpose Input/Output Port
+            #[doc = $docport]
+            ///
+            /// Every access to the group goes out as a single register access, so all of its pins
+            /// change at the same time.
+            pub struct Group<PINS> {
+                pins: PINS,
+                mask: u32,
+            }
+
+            impl<PINS: PortPins<$PTX>> Group<PINS> {
+                /// Groups `pins`, which may be a pin, a tuple of pins or an array of pins
+                pub fn new(pins: PINS) -> Self {
+                    let mask = pins.mask();
+                    Group { pins, mask }
+                }
+
+                /// Mask of the bits owned by this group in the port registers
+                pub fn mask(&self) -> u32 {
+                    self.mask
+                }
+
+                /// Releases the pins of this group
+                pub fn free(self) -> PINS {
+                    self.pins
+                }
+            }
+
+            impl<PINS: OutputPortPins<$PTX>> Group<PINS> {
+                /// Drives every pin of the group high
+                pub fn set_high(&mut self) {
+                    self.set_bits(!0);
+                }
+
+                /// Drives every pin of the group low
+                pub fn set_low(&mut self) {
+                    self.clear_bits(!0);
+                }
+
+                /// Inverts every pin of the group
+                pub fn toggle(&mut self) {
+                    self.toggle_bits(!0);
+                }
+
+                /// Drives the pins of the group whose bits are set in `bits` high
+                pub fn set_bits(&mut self, bits: u32) {
+                    let mask = self.mask;
+                    ptx().psor.write(|w| unsafe { w.bits(bits & mask) })
+                }
+
+                /// Drives the pins of the group whose bits are set in `bits` low
+                pub fn clear_bits(&mut self, bits: u32) {
+                    let mask = self.mask;
+                    ptx().pcor.write(|w| unsafe { w.bits(bits & mask) })
+                }
+
+                /// Inverts the pins of the group whose bits are set in `bits`
+                pub fn toggle_bits(&mut self, bits: u32) {
+                    let mask = self.mask;
+                    ptx().ptor.write(|w| unsafe { w.bits(bits & mask) })
+                }
+
+                /// Drives every pin of the group to the value of its bit in `bits`
+                ///
+                /// The pins are set through the PSOR then cleared through the PCOR, so the other
+                /// pins of the port are never written; the pins going low change one write after
+                /// the pins going high.
+                pub fn write(&mut self, bits: u32) {
+                    self.set_bits(bits);
+                    self.clear_bits(!bits);
+                }
+
+                /// Returns the driven value of the pins of the group, at their bit positions
+                pub fn read_output(&self) -> u32 {
+                    ptx().pdor.read().bits() & self.mask
+                }
+            }
+
+            impl<PINS: InputPortPins<$PTX>> Group<PINS> {
+                /// Returns the input value of the pins of the group, at their bit positions
+                pub fn read(&self) -> u32 {
+                    ptx().pdir.read().bits() & self.mask
+                }
             }
 
             // This pin owns its section of the PDOR, PSOR, PCOR, PTOR, and PDIR registers, as well
             // as its PCR register
             // Reference: 11.14.1 Pin Control Register n (PORTx_PCRn)
             $(
+                $(#[$pattr])*
                 #[doc = "General Purpose Input/Output Port "]
                 #[doc = $docport]
                 #[doc = " Pin "]
@@ -357,83 +1052,331 @@ macro_rules! gpio {
                     _mode: PhantomData<MODE>,
                 }
 
+                $(#[$pattr])*
+                impl<MODE> Sealed for $PTXi<MODE> {}
+
+                $(#[$pattr])*
+                impl<MODE> PinLevel for $PTXi<MODE> {
+                    fn is_level_high() -> bool {
+                        // Reference: 49.2.6 Port Data Input Register (GPIOx_PDIR)
+                        ptx().pdir.read().bits() & (1 << $i) != 0
+                    }
+                }
+
+                $(#[$pattr])*
                 impl<MODE> $PTXi<MODE> {
                     pub fn into_alternate_alt0(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT0>> {
                         set_pin_mux($i, pcr, PinMux::ALT0);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
                     pub fn into_alternate_alt1(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT1>> {
                         set_pin_mux($i, pcr, PinMux::ALT1);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
-                    pub fn into_alternate_alt2(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT2>> {
+                    pub fn into_alternate_alt2(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT2>>
+                    where
+                        Self: HasAlternate<ALT2>,
+                    {
                         set_pin_mux($i, pcr, PinMux::ALT2);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
-                    pub fn into_alternate_alt3(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT3>> {
+                    pub fn into_alternate_alt3(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT3>>
+                    where
+                        Self: HasAlternate<ALT3>,
+                    {
                         set_pin_mux($i, pcr, PinMux::ALT3);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
-                    pub fn into_alternate_alt4(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT4>> {
+                    pub fn into_alternate_alt4(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT4>>
+                    where
+                        Self: HasAlternate<ALT4>,
+                    {
                         set_pin_mux($i, pcr, PinMux::ALT4);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
-                    pub fn into_alternate_alt5(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT5>> {
+                    pub fn into_alternate_alt5(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT5>>
+                    where
+                        Self: HasAlternate<ALT5>,
+                    {
                         set_pin_mux($i, pcr, PinMux::ALT5);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
-                    pub fn into_alternate_alt6(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT6>> {
+                    pub fn into_alternate_alt6(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT6>>
+                    where
+                        Self: HasAlternate<ALT6>,
+                    {
                         set_pin_mux($i, pcr, PinMux::ALT6);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
-                    pub fn into_alternate_alt7(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT7>> {
+                    pub fn into_alternate_alt7(self, pcr: &mut PCR) -> $PTXi<Alternate<ALT7>>
+                    where
+                        Self: HasAlternate<ALT7>,
+                    {
                         set_pin_mux($i, pcr, PinMux::ALT7);
+                        clear_pin_digital_filter($i);
                         $PTXi { _mode: PhantomData }
                     }
 
                     pub fn into_push_pull_output(self, pcr: &mut PCR, pddr: &mut PDDR) -> $PTXi<Output<PushPull>> {
                         set_pin_mux($i, pcr, PinMux::ALT1);
+                        clear_pin_digital_filter($i);
                         set_pin_mode($i, pddr, PinMode::Output);
                         $PTXi { _mode: PhantomData }
                     }
+
+                    /// Returns the pin to its reset state: reset pin control (mux, pull, drive,
+                    /// filter and interrupt), input, output data cleared and digital filter disabled
+                    pub fn into_reset_state(self, pcr: &mut PCR, pddr: &mut PDDR, dfer: &mut DFER) -> $PTXi<$MODE> {
+                        // ISF is written as 0, keeping a pending flag
+                        let bits = reset_pcr(Port::$Port, $i, <$MODE as AlternateFunction>::MUX);
+                        pcr.pcr()[$i].write(|w| unsafe { w.bits(bits) });
+                        set_pin_mode($i, pddr, PinMode::Input);
+                        set_digital_filter($i, dfer, false);
+                        ptx().pcor.write(|w| unsafe { w.bits(1 << $i) });
+                        $PTXi { _mode: PhantomData }
+                    }
+
+                    /// Configures the pin as a floating input whose mode can be changed at runtime
+                    pub fn into_dynamic(self, pcr: &mut PCR, pddr: &mut PDDR) -> $PTXi<Dynamic> {
+                        set_pin_mux($i, pcr, PinMux::ALT1);
+                        clear_pin_digital_filter($i);
+                        set_pin_pull($i, pcr, PinPull::Disabled);
+                        set_pin_mode($i, pddr, PinMode::Input);
+                        $PTXi { _mode: PhantomData }
+                    }
+
+                    pub fn into_floating_input(self, pcr: &mut PCR, pddr: &mut PDDR) -> $PTXi<Input<Floating>> {
+                        set_pin_mux($i, pcr, PinMux::ALT1);
+                        clear_pin_digital_filter($i);
+                        set_pin_pull($i, pcr, PinPull::Disabled);
+                        set_pin_mode($i, pddr, PinMode::Input);
+                        $PTXi { _mode: PhantomData }
+                    }
+
+                    pub fn into_pull_down_input(self, pcr: &mut PCR, pddr: &mut PDDR) -> $PTXi<Input<PullDown>> {
+                        set_pin_mux($i, pcr, PinMux::ALT1);
+                        clear_pin_digital_filter($i);
+                        set_pin_pull($i, pcr, PinPull::Down);
+                        set_pin_mode($i, pddr, PinMode::Input);
+                        $PTXi { _mode: PhantomData }
+                    }
+
+                    pub fn into_pull_up_input(self, pcr: &mut PCR, pddr: &mut PDDR) -> $PTXi<Input<PullUp>> {
+                        set_pin_mux($i, pcr, PinMux::ALT1);
+                        clear_pin_digital_filter($i);
+                        set_pin_pull($i, pcr, PinPull::Up);
+                        set_pin_mode($i, pddr, PinMode::Input);
+                        $PTXi { _mode: PhantomData }
+                    }
+                }
+
+                $(#[$pattr])*
+                impl<MODE> $PTXi<MODE> {
+                    /// Erases the pin number from the type
+                    ///
+                    /// This is useful when you want to collect the pins of a port into an array.
+                    pub fn downgrade(self) -> PTx<MODE> {
+                        PTx { i: $i, _mode: PhantomData }
+                    }
+
+                    /// Locks the Pin Control Register of this pin until the next reset
+                    // Reference: 11.14.1 Pin Control Register n (PORTx_PCRn)
+                    pub fn lock(self, pcr: &mut PCR) -> Locked<Self> {
+                        // ISF is cleared by writing 1, so a pending flag is not written back
+                        pcr.pcr()[$i].modify(|_, w| w.isf().clear_bit().lk().set_bit());
+                        Locked { pin: self }
+                    }
+                }
+
+                $(#[$pattr])*
+                impl $PTXi<Dynamic> {
+                    /// Makes the pin a push pull output
+                    pub fn make_push_pull_output(&mut self) {
+                        set_dynamic_pin_mode($i, PinMode::Output);
+                    }
+
+                    /// Makes the pin a floating input
+                    pub fn make_floating_input(&mut self) {
+                        set_dynamic_pin_pull($i, PinPull::Disabled);
+                        set_dynamic_pin_mode($i, PinMode::Input);
+                    }
+
+                    /// Makes the pin a pulled down input
+                    pub fn make_pull_down_input(&mut self) {
+                        set_dynamic_pin_pull($i, PinPull::Down);
+                        set_dynamic_pin_mode($i, PinMode::Input);
+                    }
+
+                    /// Makes the pin a pulled up input
+                    pub fn make_pull_up_input(&mut self) {
+                        set_dynamic_pin_pull($i, PinPull::Up);
+                        set_dynamic_pin_mode($i, PinMode::Input);
+                    }
+
+                    /// Returns `true` if the pin is currently an output
+                    pub fn is_output(&self) -> bool {
+                        is_dynamic_pin_output($i)
+                    }
+
+                    /// Returns `true` if the pin is currently an input
+                    pub fn is_input(&self) -> bool {
+                        !is_dynamic_pin_output($i)
+                    }
                 }
 
+                $(#[$pattr])*
+                impl v2::OutputPin for $PTXi<Dynamic> {
+                    type Error = DynamicError;
+
+                    fn set_high(&mut self) -> Result<(), DynamicError> {
+                        if self.is_input() {
+                            return Err(DynamicError::NotOutput);
+                        }
+
+                        ptx().psor.write(|w| unsafe { w.bits(1 << $i) });
+                        Ok(())
+                    }
+
+                    fn set_low(&mut self) -> Result<(), DynamicError> {
+                        if self.is_input() {
+                            return Err(DynamicError::NotOutput);
+                        }
+
+                        ptx().pcor.write(|w| unsafe { w.bits(1 << $i) });
+                        Ok(())
+                    }
+                }
+
+                $(#[$pattr])*
+                impl v2::StatefulOutputPin for $PTXi<Dynamic> {
+                    fn is_set_high(&self) -> Result<bool, DynamicError> {
+                        if self.is_input() {
+                            return Err(DynamicError::NotOutput);
+                        }
+
+                        Ok(ptx().pdor.read().bits() & (1 << $i) != 0)
+                    }
+
+                    fn is_set_low(&self) -> Result<bool, DynamicError> {
+                        self.is_set_high().map(|high| !high)
+                    }
+                }
+
+                $(#[$pattr])*
+                impl v2::ToggleableOutputPin for $PTXi<Dynamic> {
+                    type Error = DynamicError;
+
+                    fn toggle(&mut self) -> Result<(), DynamicError> {
+                        if self.is_input() {
+                            return Err(DynamicError::NotOutput);
+                        }
+
+                        ptx().ptor.write(|w| unsafe { w.bits(1 << $i) });
+                        Ok(())
+                    }
+                }
+
+                $(#[$pattr])*
+                impl v2::InputPin for $PTXi<Dynamic> {
+                    type Error = DynamicError;
+
+                    fn is_high(&self) -> Result<bool, DynamicError> {
+                        if self.is_output() {
+                            return Err(DynamicError::NotInput);
+                        }
+
+                        Ok(ptx().pdir.read().bits() & (1 << $i) != 0)
+                    }
+
+                    fn is_low(&self) -> Result<bool, DynamicError> {
+                        self.is_high().map(|high| !high)
+                    }
+                }
+
+                $(#[$pattr])*
+                impl<MODE: Filterable> $PTXi<Input<MODE>> {
+                    /// Passes the input through the port's digital filter
+                    ///
+                    /// The filter clock and width are shared by every pin of the port, see
+                    /// `DFCR::set_clock` and `DFWR::set_width`.
+                    pub fn into_filtered_input(self, dfer: &mut DFER) -> $PTXi<Input<Filtered<MODE>>> {
+                        set_digital_filter($i, dfer, true);
+                        $PTXi { _mode: PhantomData }
+                    }
+                }
+
+                $(#[$pattr])*
+                impl<MODE> $PTXi<Input<Filtered<MODE>>> {
+                    /// Bypasses the port's digital filter
+                    pub fn into_unfiltered_input(self, dfer: &mut DFER) -> $PTXi<Input<MODE>> {
+                        set_digital_filter($i, dfer, false);
+                        $PTXi { _mode: PhantomData }
+                    }
+                }
+
+                $(#[$pattr])*
                 impl<MODE> StatefulOutputPin for $PTXi<Output<MODE>> {
                     fn is_set_high(&self) -> bool {
-                        !self.is_set_low()
+                        ptx().pdor.read().bits() & (1 << $i) != 0
                     }
 
                     fn is_set_low(&self) -> bool {
-                        (PDOR { _0: () }).pdor().read().bits() & (1 << $i) == 0
+                        !self.is_set_high()
                     }
                 }
 
+                $(#[$pattr])*
                 impl<MODE> OutputPin for $PTXi<Output<MODE>> {
                     fn set_high(&mut self) {
-                        (PSOR { _0: () }).psor().write(|w| unsafe { w.bits(1 << $i) })
+                        ptx().psor.write(|w| unsafe { w.bits(1 << $i) })
                     }
 
                     fn set_low(&mut self) {
-                        (PCOR { _0: () }).pcor().write(|w| unsafe { w.bits(1 << $i) })
+                        ptx().pcor.write(|w| unsafe { w.bits(1 << $i) })
+                    }
+                }
+
+                $(#[$pattr])*
+                impl<MODE> ToggleableOutputPin for $PTXi<Output<MODE>> {
+                    fn toggle(&mut self) {
+                        ptx().ptor.write(|w| unsafe { w.bits(1 << $i) })
+                    }
+                }
+
+                $(#[$pattr])*
+                impl<MODE> PortPins<$PTX> for $PTXi<MODE> {
+                    fn mask(&self) -> u32 {
+                        1 << $i
                     }
                 }
 
-                impl<MODE> toggleable::Default for $PTXi<Output<MODE>> {}
+                $(#[$pattr])*
+                impl<MODE> OutputPortPins<$PTX> for $PTXi<Output<MODE>> {}
+                $(#[$pattr])*
+                impl<MODE> InputPortPins<$PTX> for $PTXi<Input<MODE>> {}
 
+                $(#[$pattr])*
                 impl<MODE> InputPin for $PTXi<Input<MODE>> {
                     fn is_high(&self) -> bool {
                         !self.is_low()
                     }
 
                     fn is_low(&self) -> bool {
-                        (PDIR { _0: () }).pdir().read().bits() & (1 << $i) == 0
+                        ptx().pdir.read().bits() & (1 << $i) == 0
                     }
                 }
             )+
@@ -442,57 +1385,181 @@ macro_rules! gpio {
 }
 
 // Reference: 10.3.1 K20 Signal Multiplexing and Pin Assignments
-gpio!(PORTA, porta, PTA, pta, gpioa, "A", [
-      PTA0: (pta0, 0, Input<Floating>, "0"),
-      PTA1: (pta1, 1, Input<Floating>, "1"),
-      PTA2: (pta2, 2, Input<Floating>, "2"),
-      PTA3: (pta3, 3, Input<Floating>, "3"),
-      PTA4: (pta4, 4, Input<Floating>, "4"),
-      PTA5: (pta5, 5, Input<Floating>, "5"),
-      PTA12: (pta12, 12, Input<Floating>, "12"),
-      PTA13: (pta13, 13, Input<Floating>, "13"),
-      PTA18: (pta18, 18, Input<Floating>, "18"),
-      PTA19: (pta19, 19, Input<Floating>, "19"),
+//
+// Pins present on the 64 LQFP package are always available, the package features add the pins of
+// the larger packages; every package contains all pins of the smaller ones. Pins start in their
+// reset pin mux: JTAG / SWD and NMI on PTA0 to PTA4, disabled or analog everywhere else.
+gpio!(PORTA, porta, PTA, pta, gpioa, A, "A", [
+      PTA0: (pta0, 0, Alternate<ALT7>, "0"),
+      PTA1: (pta1, 1, Alternate<ALT7>, "1"),
+      PTA2: (pta2, 2, Alternate<ALT7>, "2"),
+      PTA3: (pta3, 3, Alternate<ALT7>, "3"),
+      PTA4: (pta4, 4, Alternate<ALT7>, "4"),
+      PTA5: (pta5, 5, Alternate<ALT0>, "5"),
+      #[cfg(feature = "121mapbga")]
+      PTA6: (pta6, 6, Alternate<ALT0>, "6"),
+      #[cfg(feature = "121mapbga")]
+      PTA7: (pta7, 7, Alternate<ALT0>, "7"),
+      #[cfg(feature = "121mapbga")]
+      PTA8: (pta8, 8, Alternate<ALT0>, "8"),
+      #[cfg(feature = "121mapbga")]
+      PTA9: (pta9, 9, Alternate<ALT0>, "9"),
+      #[cfg(feature = "121mapbga")]
+      PTA10: (pta10, 10, Alternate<ALT0>, "10"),
+      #[cfg(feature = "121mapbga")]
+      PTA11: (pta11, 11, Alternate<ALT0>, "11"),
+      PTA12: (pta12, 12, Alternate<ALT0>, "12"),
+      PTA13: (pta13, 13, Alternate<ALT0>, "13"),
+      #[cfg(feature = "100lqfp")]
+      PTA14: (pta14, 14, Alternate<ALT0>, "14"),
+      #[cfg(feature = "100lqfp")]
+      PTA15: (pta15, 15, Alternate<ALT0>, "15"),
+      #[cfg(feature = "100lqfp")]
+      PTA16: (pta16, 16, Alternate<ALT0>, "16"),
+      #[cfg(feature = "100lqfp")]
+      PTA17: (pta17, 17, Alternate<ALT0>, "17"),
+      PTA18: (pta18, 18, Alternate<ALT0>, "18"),
+      PTA19: (pta19, 19, Alternate<ALT0>, "19"),
+      #[cfg(feature = "144lqfp")]
+      PTA24: (pta24, 24, Alternate<ALT0>, "24"),
+      #[cfg(feature = "144lqfp")]
+      PTA25: (pta25, 25, Alternate<ALT0>, "25"),
+      #[cfg(feature = "144lqfp")]
+      PTA26: (pta26, 26, Alternate<ALT0>, "26"),
+      #[cfg(feature = "144lqfp")]
+      PTA27: (pta27, 27, Alternate<ALT0>, "27"),
+      #[cfg(feature = "144lqfp")]
+      PTA28: (pta28, 28, Alternate<ALT0>, "28"),
+      #[cfg(feature = "144lqfp")]
+      PTA29: (pta29, 29, Alternate<ALT0>, "29"),
 ]);
 
-gpio!(PORTB, portb, PTB, ptb, gpiob, "B", [
-      PTB0: (ptb0, 0, Input<Floating>, "0"),
-      PTB1: (ptb1, 1, Input<Floating>, "1"),
-      PTB2: (ptb2, 2, Input<Floating>, "2"),
-      PTB3: (ptb3, 3, Input<Floating>, "3"),
-      PTB16: (ptb16, 16, Input<Floating>, "16"),
-      PTB17: (ptb17, 17, Input<Floating>, "17"),
-      PTB18: (ptb18, 18, Input<Floating>, "18"),
-      PTB19: (ptb19, 19, Input<Floating>, "19"),
+gpio!(PORTB, portb, PTB, ptb, gpiob, B, "B", [
+      PTB0: (ptb0, 0, Alternate<ALT0>, "0"),
+      PTB1: (ptb1, 1, Alternate<ALT0>, "1"),
+      PTB2: (ptb2, 2, Alternate<ALT0>, "2"),
+      PTB3: (ptb3, 3, Alternate<ALT0>, "3"),
+      #[cfg(feature = "121mapbga")]
+      PTB4: (ptb4, 4, Alternate<ALT0>, "4"),
+      #[cfg(feature = "121mapbga")]
+      PTB5: (ptb5, 5, Alternate<ALT0>, "5"),
+      #[cfg(feature = "121mapbga")]
+      PTB6: (ptb6, 6, Alternate<ALT0>, "6"),
+      #[cfg(feature = "121mapbga")]
+      PTB7: (ptb7, 7, Alternate<ALT0>, "7"),
+      #[cfg(feature = "121mapbga")]
+      PTB8: (ptb8, 8, Alternate<ALT0>, "8"),
+      #[cfg(feature = "100lqfp")]
+      PTB9: (ptb9, 9, Alternate<ALT0>, "9"),
+      #[cfg(feature = "100lqfp")]
+      PTB10: (ptb10, 10, Alternate<ALT0>, "10"),
+      #[cfg(feature = "100lqfp")]
+      PTB11: (ptb11, 11, Alternate<ALT0>, "11"),
+      PTB16: (ptb16, 16, Alternate<ALT0>, "16"),
+      PTB17: (ptb17, 17, Alternate<ALT0>, "17"),
+      PTB18: (ptb18, 18, Alternate<ALT0>, "18"),
+      PTB19: (ptb19, 19, Alternate<ALT0>, "19"),
+      #[cfg(feature = "100lqfp")]
+      PTB20: (ptb20, 20, Alternate<ALT0>, "20"),
+      #[cfg(feature = "100lqfp")]
+      PTB21: (ptb21, 21, Alternate<ALT0>, "21"),
+      #[cfg(feature = "100lqfp")]
+      PTB22: (ptb22, 22, Alternate<ALT0>, "22"),
+      #[cfg(feature = "100lqfp")]
+      PTB23: (ptb23, 23, Alternate<ALT0>, "23"),
 ]);
 
-gpio!(PORTC, portc, PTC, ptc, gpioc, "C", [
-      PTC0: (ptc0, 0, Input<Floating>, "0"),
-      PTC1: (ptc1, 1, Input<Floating>, "1"),
-      PTC2: (ptc2, 2, Input<Floating>, "2"),
-      PTC3: (ptc3, 3, Input<Floating>, "3"),
-      PTC4: (ptc4, 4, Input<Floating>, "4"),
-      PTC5: (ptc5, 5, Input<Floating>, "5"),
-      PTC6: (ptc6, 6, Input<Floating>, "6"),
-      PTC7: (ptc7, 7, Input<Floating>, "7"),
-      PTC8: (ptc8, 8, Input<Floating>, "8"),
-      PTC9: (ptc9, 9, Input<Floating>, "9"),
-      PTC10: (ptc10, 10, Input<Floating>, "10"),
-      PTC11: (ptc11, 11, Input<Floating>, "11"),
+gpio!(PORTC, portc, PTC, ptc, gpioc, C, "C", [
+      PTC0: (ptc0, 0, Alternate<ALT0>, "0"),
+      PTC1: (ptc1, 1, Alternate<ALT0>, "1"),
+      PTC2: (ptc2, 2, Alternate<ALT0>, "2"),
+      PTC3: (ptc3, 3, Alternate<ALT0>, "3"),
+      PTC4: (ptc4, 4, Alternate<ALT0>, "4"),
+      PTC5: (ptc5, 5, Alternate<ALT0>, "5"),
+      PTC6: (ptc6, 6, Alternate<ALT0>, "6"),
+      PTC7: (ptc7, 7, Alternate<ALT0>, "7"),
+      PTC8: (ptc8, 8, Alternate<ALT0>, "8"),
+      PTC9: (ptc9, 9, Alternate<ALT0>, "9"),
+      PTC10: (ptc10, 10, Alternate<ALT0>, "10"),
+      PTC11: (ptc11, 11, Alternate<ALT0>, "11"),
+      #[cfg(feature = "100lqfp")]
+      PTC12: (ptc12, 12, Alternate<ALT0>, "12"),
+      #[cfg(feature = "100lqfp")]
+      PTC13: (ptc13, 13, Alternate<ALT0>, "13"),
+      #[cfg(feature = "100lqfp")]
+      PTC14: (ptc14, 14, Alternate<ALT0>, "14"),
+      #[cfg(feature = "100lqfp")]
+      PTC15: (ptc15, 15, Alternate<ALT0>, "15"),
+      #[cfg(feature = "100lqfp")]
+      PTC16: (ptc16, 16, Alternate<ALT0>, "16"),
+      #[cfg(feature = "100lqfp")]
+      PTC17: (ptc17, 17, Alternate<ALT0>, "17"),
+      #[cfg(feature = "100lqfp")]
+      PTC18: (ptc18, 18, Alternate<ALT0>, "18"),
+      #[cfg(feature = "121mapbga")]
+      PTC19: (ptc19, 19, Alternate<ALT0>, "19"),
 ]);
 
-gpio!(PORTD, portd, PTD, ptd, gpiod, "D", [
-      PTD0: (ptd0, 0, Input<Floating>, "0"),
-      PTD1: (ptd1, 1, Input<Floating>, "1"),
-      PTD2: (ptd2, 2, Input<Floating>, "2"),
-      PTD3: (ptd3, 3, Input<Floating>, "3"),
-      PTD4: (ptd4, 4, Input<Floating>, "4"),
-      PTD5: (ptd5, 5, Input<Floating>, "5"),
-      PTD6: (ptd6, 6, Input<Floating>, "6"),
-      PTD7: (ptd7, 7, Input<Floating>, "7"),
+gpio!(PORTD, portd, PTD, ptd, gpiod, D, "D", [
+      PTD0: (ptd0, 0, Alternate<ALT0>, "0"),
+      PTD1: (ptd1, 1, Alternate<ALT0>, "1"),
+      PTD2: (ptd2, 2, Alternate<ALT0>, "2"),
+      PTD3: (ptd3, 3, Alternate<ALT0>, "3"),
+      PTD4: (ptd4, 4, Alternate<ALT0>, "4"),
+      PTD5: (ptd5, 5, Alternate<ALT0>, "5"),
+      PTD6: (ptd6, 6, Alternate<ALT0>, "6"),
+      PTD7: (ptd7, 7, Alternate<ALT0>, "7"),
+      #[cfg(feature = "121mapbga")]
+      PTD8: (ptd8, 8, Alternate<ALT0>, "8"),
+      #[cfg(feature = "121mapbga")]
+      PTD9: (ptd9, 9, Alternate<ALT0>, "9"),
+      #[cfg(feature = "121mapbga")]
+      PTD10: (ptd10, 10, Alternate<ALT0>, "10"),
+      #[cfg(feature = "121mapbga")]
+      PTD11: (ptd11, 11, Alternate<ALT0>, "11"),
+      #[cfg(feature = "121mapbga")]
+      PTD12: (ptd12, 12, Alternate<ALT0>, "12"),
+      #[cfg(feature = "121mapbga")]
+      PTD13: (ptd13, 13, Alternate<ALT0>, "13"),
+      #[cfg(feature = "121mapbga")]
+      PTD14: (ptd14, 14, Alternate<ALT0>, "14"),
+      #[cfg(feature = "121mapbga")]
+      PTD15: (ptd15, 15, Alternate<ALT0>, "15"),
 ]);
 
-gpio!(PORTE, porte, PTE, pte, gpioe, "E", [
-      PTE0: (pte0, 0, Input<Floating>, "0"),
-      PTE1: (pte1, 1, Input<Floating>, "1"),
+gpio!(PORTE, porte, PTE, pte, gpioe, E, "E", [
+      PTE0: (pte0, 0, Alternate<ALT0>, "0"),
+      PTE1: (pte1, 1, Alternate<ALT0>, "1"),
+      #[cfg(feature = "100lqfp")]
+      PTE2: (pte2, 2, Alternate<ALT0>, "2"),
+      #[cfg(feature = "100lqfp")]
+      PTE3: (pte3, 3, Alternate<ALT0>, "3"),
+      #[cfg(feature = "100lqfp")]
+      PTE4: (pte4, 4, Alternate<ALT0>, "4"),
+      #[cfg(feature = "100lqfp")]
+      PTE5: (pte5, 5, Alternate<ALT0>, "5"),
+      #[cfg(feature = "100lqfp")]
+      PTE6: (pte6, 6, Alternate<ALT0>, "6"),
+      #[cfg(feature = "144lqfp")]
+      PTE7: (pte7, 7, Alternate<ALT0>, "7"),
+      #[cfg(feature = "144lqfp")]
+      PTE8: (pte8, 8, Alternate<ALT0>, "8"),
+      #[cfg(feature = "144lqfp")]
+      PTE9: (pte9, 9, Alternate<ALT0>, "9"),
+      #[cfg(feature = "144lqfp")]
+      PTE10: (pte10, 10, Alternate<ALT0>, "10"),
+      #[cfg(feature = "144lqfp")]
+      PTE11: (pte11, 11, Alternate<ALT0>, "11"),
+      #[cfg(feature = "144lqfp")]
+      PTE12: (pte12, 12, Alternate<ALT0>, "12"),
+      #[cfg(feature = "100lqfp")]
+      PTE24: (pte24, 24, Alternate<ALT0>, "24"),
+      #[cfg(feature = "100lqfp")]
+      PTE25: (pte25, 25, Alternate<ALT0>, "25"),
+      #[cfg(feature = "100lqfp")]
+      PTE26: (pte26, 26, Alternate<ALT0>, "26"),
+      #[cfg(feature = "144lqfp")]
+      PTE27: (pte27, 27, Alternate<ALT0>, "27"),
+      #[cfg(feature = "144lqfp")]
+      PTE28: (pte28, 28, Alternate<ALT0>, "28"),
 ]);
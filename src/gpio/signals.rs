@@ -0,0 +1,688 @@
+//! Peripheral signals of the pins, by alternate function
+//!
+//! A pin put into the alternate function carrying a peripheral signal implements the marker trait
+//! of that signal, e.g. `PTA1<Alternate<ALT2>>` implements `UartRx<UART0>`. Drivers should bound
+//! their pins by these traits instead of by alternate function numbers.
+
+use mk20d7::{
+    ADC0, ADC1, CAN0, CMP0, CMP1, FTM0, FTM1, FTM2, I2C0, I2C1, I2S0, SPI0, SPI1, UART0, UART1,
+    UART2, USB0,
+};
+#[cfg(feature = "100lqfp")]
+use mk20d7::{CMP2, UART3, UART4};
+
+use super::private::Sealed;
+use super::{
+    Alternate, HasAlternate, ALT0, ALT2, ALT3, ALT4, ALT5, ALT6, ALT7,
+    gpioa, gpiob, gpioc, gpiod, gpioe,
+};
+
+// UART
+
+/// UART transmit data
+pub trait UartTx<UART>: Sealed {}
+
+/// UART receive data
+pub trait UartRx<UART>: Sealed {}
+
+/// UART clear to send (or collision detect)
+pub trait UartCts<UART>: Sealed {}
+
+/// UART request to send (or transmitter driver enable)
+pub trait UartRts<UART>: Sealed {}
+
+// SPI
+
+/// SPI serial clock
+pub trait SpiSck<SPI>: Sealed {}
+
+/// SPI serial data out
+pub trait SpiSout<SPI>: Sealed {}
+
+/// SPI serial data in
+pub trait SpiSin<SPI>: Sealed {}
+
+/// SPI peripheral chip select
+pub trait SpiPcs<SPI>: Sealed {
+    /// Chip select number
+    const INDEX: u8;
+}
+
+// I2C
+
+/// I2C serial clock
+pub trait I2cScl<I2C>: Sealed {}
+
+/// I2C serial data
+pub trait I2cSda<I2C>: Sealed {}
+
+// FTM
+
+/// FlexTimer channel
+pub trait FtmChannel<FTM>: Sealed {
+    /// Channel number
+    const INDEX: u8;
+}
+
+/// FlexTimer fault input
+pub trait FtmFault<FTM>: Sealed {
+    /// Fault input number
+    const INDEX: u8;
+}
+
+/// FlexTimer quadrature decoder phase A input
+pub trait FtmQdPha<FTM>: Sealed {}
+
+/// FlexTimer quadrature decoder phase B input
+pub trait FtmQdPhb<FTM>: Sealed {}
+
+// ADC
+
+/// ADC single ended input, selected with the `a` channel mux
+pub trait AdcChannel<ADC>: Sealed {
+    /// Input channel number
+    const INDEX: u8;
+}
+
+/// ADC single ended input, selected with the `b` channel mux
+pub trait AdcChannelB<ADC>: Sealed {
+    /// Input channel number
+    const INDEX: u8;
+}
+
+// CAN
+
+/// CAN transmit
+pub trait CanTx<CAN>: Sealed {}
+
+/// CAN receive
+pub trait CanRx<CAN>: Sealed {}
+
+// I2S
+
+/// I2S master clock
+pub trait I2sMclk<I2S>: Sealed {}
+
+/// I2S transmit bit clock
+pub trait I2sTxBclk<I2S>: Sealed {}
+
+/// I2S transmit frame sync
+pub trait I2sTxFs<I2S>: Sealed {}
+
+/// I2S transmit data
+pub trait I2sTxd<I2S>: Sealed {
+    /// Data line number
+    const INDEX: u8;
+}
+
+/// I2S receive bit clock
+pub trait I2sRxBclk<I2S>: Sealed {}
+
+/// I2S receive frame sync
+pub trait I2sRxFs<I2S>: Sealed {}
+
+/// I2S receive data
+pub trait I2sRxd<I2S>: Sealed {
+    /// Data line number
+    const INDEX: u8;
+}
+
+// USB
+
+/// USB start of frame output
+pub trait UsbSofOut<USB>: Sealed {}
+
+/// USB clock input
+pub trait UsbClkin<USB>: Sealed {}
+
+// CMP
+
+/// Comparator input
+pub trait CmpInput<CMP>: Sealed {
+    /// Input number
+    const INDEX: u8;
+}
+
+/// Comparator output
+pub trait CmpOutput<CMP>: Sealed {}
+
+macro_rules! pins {
+    ($(
+        $gpiox:ident::$PTXi:ident: [$(
+            $ALT:ident: [$($Signal:ident<$PERIPH:ident>$(($index:expr))*),*],
+        )*],
+    )+) => {
+        $(
+            $(
+                impl<MODE> HasAlternate<$ALT> for $gpiox::$PTXi<MODE> {}
+
+                $(
+                    impl $Signal<$PERIPH> for $gpiox::$PTXi<Alternate<$ALT>> {
+                        $(const INDEX: u8 = $index;)*
+                    }
+                )*
+            )*
+        )+
+    }
+}
+
+// Reference: 10.3.1 K20 Signal Multiplexing and Pin Assignments
+//
+// Alternate functions without a typed signal are listed with the name of their signal so that the
+// pin still offers them. ALT0 and ALT1 are available on every pin.
+
+// 64 LQFP
+pins! {
+    gpioa::PTA0: [
+        ALT0: [], // TSI0_CH1
+        ALT2: [UartCts<UART0>],
+        ALT3: [FtmChannel<FTM0>(5)],
+        ALT7: [], // JTAG_TCLK / SWD_CLK / EZP_CLK
+    ],
+    gpioa::PTA1: [
+        ALT0: [], // TSI0_CH2
+        ALT2: [UartRx<UART0>],
+        ALT3: [FtmChannel<FTM0>(6)],
+        ALT7: [], // JTAG_TDI / EZP_DI
+    ],
+    gpioa::PTA2: [
+        ALT0: [], // TSI0_CH3
+        ALT2: [UartTx<UART0>],
+        ALT3: [FtmChannel<FTM0>(7)],
+        ALT7: [], // JTAG_TDO / TRACE_SWO / EZP_DO
+    ],
+    gpioa::PTA3: [
+        ALT0: [], // TSI0_CH4
+        ALT2: [UartRts<UART0>],
+        ALT3: [FtmChannel<FTM0>(0)],
+        ALT7: [], // JTAG_TMS / SWD_DIO
+    ],
+    gpioa::PTA4: [
+        ALT0: [], // TSI0_CH5
+        ALT3: [FtmChannel<FTM0>(1)],
+        ALT7: [], // NMI_b / EZP_CS_b
+    ],
+    gpioa::PTA5: [
+        ALT3: [FtmChannel<FTM0>(2)],
+        ALT6: [I2sTxBclk<I2S0>],
+        ALT7: [], // JTAG_TRST_b
+    ],
+    gpioa::PTA12: [
+        ALT2: [CanTx<CAN0>],
+        ALT3: [FtmChannel<FTM1>(0)],
+        ALT6: [I2sTxd<I2S0>(0)],
+        ALT7: [FtmQdPha<FTM1>],
+    ],
+    gpioa::PTA13: [
+        ALT2: [CanRx<CAN0>],
+        ALT3: [FtmChannel<FTM1>(1)],
+        ALT6: [I2sTxFs<I2S0>],
+        ALT7: [FtmQdPhb<FTM1>],
+    ],
+    gpioa::PTA18: [
+        ALT0: [], // EXTAL0
+        ALT3: [FtmFault<FTM0>(2)],
+        ALT4: [], // FTM_CLKIN0
+    ],
+    gpioa::PTA19: [
+        ALT0: [], // XTAL0
+        ALT3: [FtmFault<FTM1>(0)],
+        ALT4: [], // FTM_CLKIN1
+        ALT6: [], // LPTMR0_ALT1
+    ],
+    gpiob::PTB0: [
+        ALT0: [AdcChannel<ADC0>(8), AdcChannel<ADC1>(8)], // TSI0_CH0
+        ALT2: [I2cScl<I2C0>],
+        ALT3: [FtmChannel<FTM1>(0)],
+        ALT6: [FtmQdPha<FTM1>],
+    ],
+    gpiob::PTB1: [
+        ALT0: [AdcChannel<ADC0>(9), AdcChannel<ADC1>(9)], // TSI0_CH6
+        ALT2: [I2cSda<I2C0>],
+        ALT3: [FtmChannel<FTM1>(1)],
+        ALT6: [FtmQdPhb<FTM1>],
+    ],
+    gpiob::PTB2: [
+        ALT0: [AdcChannel<ADC0>(12)], // TSI0_CH7
+        ALT2: [I2cScl<I2C0>],
+        ALT3: [UartRts<UART0>],
+        ALT6: [FtmFault<FTM0>(3)],
+    ],
+    gpiob::PTB3: [
+        ALT0: [AdcChannel<ADC0>(13)], // TSI0_CH8
+        ALT2: [I2cSda<I2C0>],
+        ALT3: [UartCts<UART0>],
+        ALT6: [FtmFault<FTM0>(0)],
+    ],
+    gpiob::PTB16: [
+        ALT0: [], // TSI0_CH9
+        ALT3: [UartRx<UART0>],
+        ALT7: [], // EWM_IN
+    ],
+    gpiob::PTB17: [
+        ALT0: [], // TSI0_CH10
+        ALT3: [UartTx<UART0>],
+        ALT7: [], // EWM_OUT_b
+    ],
+    gpiob::PTB18: [
+        ALT0: [], // TSI0_CH11
+        ALT2: [CanTx<CAN0>],
+        ALT3: [FtmChannel<FTM2>(0)],
+        ALT4: [I2sTxBclk<I2S0>],
+        ALT6: [FtmQdPha<FTM2>],
+    ],
+    gpiob::PTB19: [
+        ALT0: [], // TSI0_CH12
+        ALT2: [CanRx<CAN0>],
+        ALT3: [FtmChannel<FTM2>(1)],
+        ALT4: [I2sTxFs<I2S0>],
+        ALT6: [FtmQdPhb<FTM2>],
+    ],
+    gpioc::PTC0: [
+        ALT0: [AdcChannel<ADC0>(14)], // TSI0_CH13
+        ALT2: [SpiPcs<SPI0>(4)],
+        ALT3: [], // PDB0_EXTRG
+        ALT6: [I2sTxd<I2S0>(1)],
+    ],
+    gpioc::PTC1: [
+        ALT0: [AdcChannel<ADC0>(15)], // TSI0_CH14
+        ALT2: [SpiPcs<SPI0>(3)],
+        ALT3: [UartRts<UART1>],
+        ALT4: [FtmChannel<FTM0>(0)],
+        ALT6: [I2sTxd<I2S0>(0)],
+    ],
+    gpioc::PTC2: [
+        ALT0: [AdcChannelB<ADC0>(4), CmpInput<CMP1>(0)], // TSI0_CH15
+        ALT2: [SpiPcs<SPI0>(2)],
+        ALT3: [UartCts<UART1>],
+        ALT4: [FtmChannel<FTM0>(1)],
+        ALT6: [I2sTxFs<I2S0>],
+    ],
+    gpioc::PTC3: [
+        ALT0: [CmpInput<CMP1>(1)],
+        ALT2: [SpiPcs<SPI0>(1)],
+        ALT3: [UartRx<UART1>],
+        ALT4: [FtmChannel<FTM0>(2)],
+        ALT5: [], // CLKOUT
+        ALT6: [I2sTxBclk<I2S0>],
+    ],
+    gpioc::PTC4: [
+        ALT2: [SpiPcs<SPI0>(0)],
+        ALT3: [UartTx<UART1>],
+        ALT4: [FtmChannel<FTM0>(3)],
+        ALT6: [CmpOutput<CMP1>],
+    ],
+    gpioc::PTC5: [
+        ALT2: [SpiSck<SPI0>],
+        ALT3: [], // LPTMR0_ALT2
+        ALT4: [I2sRxd<I2S0>(0)],
+        ALT6: [CmpOutput<CMP0>],
+    ],
+    gpioc::PTC6: [
+        ALT0: [CmpInput<CMP0>(0)],
+        ALT2: [SpiSout<SPI0>],
+        ALT3: [], // PDB0_EXTRG
+        ALT4: [I2sRxBclk<I2S0>],
+        ALT6: [I2sMclk<I2S0>],
+    ],
+    gpioc::PTC7: [
+        ALT0: [CmpInput<CMP0>(1)],
+        ALT2: [SpiSin<SPI0>],
+        ALT3: [UsbSofOut<USB0>],
+        ALT4: [I2sRxFs<I2S0>],
+    ],
+    gpioc::PTC8: [
+        ALT0: [AdcChannelB<ADC1>(4), CmpInput<CMP0>(2)],
+        ALT4: [I2sMclk<I2S0>],
+    ],
+    gpioc::PTC9: [
+        ALT0: [AdcChannelB<ADC1>(5), CmpInput<CMP0>(3)],
+        ALT4: [I2sRxBclk<I2S0>],
+        ALT6: [FtmFault<FTM2>(0)],
+    ],
+    gpioc::PTC10: [
+        ALT0: [AdcChannelB<ADC1>(6)],
+        ALT2: [I2cScl<I2C1>],
+        ALT4: [I2sRxFs<I2S0>],
+    ],
+    gpioc::PTC11: [
+        ALT0: [AdcChannelB<ADC1>(7)],
+        ALT2: [I2cSda<I2C1>],
+        ALT4: [I2sRxd<I2S0>(1)],
+    ],
+    gpiod::PTD0: [
+        ALT2: [SpiPcs<SPI0>(0)],
+        ALT3: [UartRts<UART2>],
+    ],
+    gpiod::PTD1: [
+        ALT0: [AdcChannelB<ADC0>(5)],
+        ALT2: [SpiSck<SPI0>],
+        ALT3: [UartCts<UART2>],
+    ],
+    gpiod::PTD2: [
+        ALT2: [SpiSout<SPI0>],
+        ALT3: [UartRx<UART2>],
+    ],
+    gpiod::PTD3: [
+        ALT2: [SpiSin<SPI0>],
+        ALT3: [UartTx<UART2>],
+    ],
+    gpiod::PTD4: [
+        ALT2: [SpiPcs<SPI0>(1)],
+        ALT3: [UartRts<UART0>],
+        ALT4: [FtmChannel<FTM0>(4)],
+        ALT6: [], // EWM_IN
+    ],
+    gpiod::PTD5: [
+        ALT0: [AdcChannelB<ADC0>(6)],
+        ALT2: [SpiPcs<SPI0>(2)],
+        ALT3: [UartCts<UART0>],
+        ALT4: [FtmChannel<FTM0>(5)],
+        ALT6: [], // EWM_OUT_b
+    ],
+    gpiod::PTD6: [
+        ALT0: [AdcChannelB<ADC0>(7)],
+        ALT2: [SpiPcs<SPI0>(3)],
+        ALT3: [UartRx<UART0>],
+        ALT4: [FtmChannel<FTM0>(6)],
+        ALT6: [FtmFault<FTM0>(0)],
+    ],
+    gpiod::PTD7: [
+        ALT2: [], // CMT_IRO
+        ALT3: [UartTx<UART0>],
+        ALT4: [FtmChannel<FTM0>(7)],
+        ALT6: [FtmFault<FTM0>(1)],
+    ],
+    gpioe::PTE0: [
+        ALT0: [AdcChannel<ADC1>(4)],
+        ALT2: [SpiPcs<SPI1>(1)],
+        ALT3: [UartTx<UART1>],
+        ALT6: [I2cSda<I2C1>],
+        ALT7: [], // RTC_CLKOUT
+    ],
+    gpioe::PTE1: [
+        ALT0: [AdcChannel<ADC1>(5)],
+        ALT2: [SpiSout<SPI1>],
+        ALT3: [UartRx<UART1>],
+        ALT6: [I2cScl<I2C1>],
+        ALT7: [SpiSin<SPI1>],
+    ],
+}
+
+// 100 LQFP
+#[cfg(feature = "100lqfp")]
+pins! {
+    gpioa::PTA14: [
+        ALT2: [SpiPcs<SPI0>(0)],
+        ALT3: [UartTx<UART0>],
+        ALT6: [I2sRxBclk<I2S0>],
+        ALT7: [I2sTxd<I2S0>(1)],
+    ],
+    gpioa::PTA15: [
+        ALT2: [SpiSck<SPI0>],
+        ALT3: [UartRx<UART0>],
+        ALT6: [I2sRxd<I2S0>(0)],
+    ],
+    gpioa::PTA16: [
+        ALT2: [SpiSout<SPI0>],
+        ALT3: [UartCts<UART0>],
+        ALT6: [I2sRxFs<I2S0>],
+        ALT7: [I2sRxd<I2S0>(1)],
+    ],
+    gpioa::PTA17: [
+        ALT0: [AdcChannel<ADC1>(17)],
+        ALT2: [SpiSin<SPI0>],
+        ALT3: [UartRts<UART0>],
+        ALT6: [I2sMclk<I2S0>],
+    ],
+    gpiob::PTB9: [
+        ALT2: [SpiPcs<SPI1>(1)],
+        ALT3: [UartCts<UART3>],
+        ALT5: [], // FB_AD20
+    ],
+    gpiob::PTB10: [
+        ALT0: [AdcChannel<ADC1>(14)],
+        ALT2: [SpiPcs<SPI1>(0)],
+        ALT3: [UartRx<UART3>],
+        ALT5: [], // FB_AD19
+        ALT6: [FtmFault<FTM0>(1)],
+    ],
+    gpiob::PTB11: [
+        ALT0: [AdcChannel<ADC1>(15)],
+        ALT2: [SpiSck<SPI1>],
+        ALT3: [UartTx<UART3>],
+        ALT5: [], // FB_AD18
+        ALT6: [FtmFault<FTM0>(2)],
+    ],
+    gpiob::PTB20: [
+        ALT2: [], // SPI2_PCS0
+        ALT5: [], // FB_AD31
+        ALT6: [CmpOutput<CMP0>],
+    ],
+    gpiob::PTB21: [
+        ALT2: [], // SPI2_SCK
+        ALT5: [], // FB_AD30
+        ALT6: [CmpOutput<CMP1>],
+    ],
+    gpiob::PTB22: [
+        ALT2: [], // SPI2_SOUT
+        ALT5: [], // FB_AD29
+        ALT6: [CmpOutput<CMP2>],
+    ],
+    gpiob::PTB23: [
+        ALT2: [], // SPI2_SIN
+        ALT3: [SpiPcs<SPI0>(5)],
+        ALT5: [], // FB_AD28
+    ],
+    gpioc::PTC12: [
+        ALT3: [UartRts<UART4>],
+        ALT5: [], // FB_AD27
+    ],
+    gpioc::PTC13: [
+        ALT3: [UartCts<UART4>],
+        ALT5: [], // FB_AD26
+    ],
+    gpioc::PTC14: [
+        ALT3: [UartRx<UART4>],
+        ALT5: [], // FB_AD25
+    ],
+    gpioc::PTC15: [
+        ALT3: [UartTx<UART4>],
+        ALT5: [], // FB_AD24
+    ],
+    gpioc::PTC16: [
+        ALT3: [UartRx<UART3>],
+        ALT5: [], // FB_CS5_b
+    ],
+    gpioc::PTC17: [
+        ALT3: [UartTx<UART3>],
+        ALT5: [], // FB_CS4_b
+    ],
+    gpioc::PTC18: [
+        ALT3: [UartRts<UART3>],
+        ALT5: [], // FB_TBST_b
+    ],
+    gpioe::PTE2: [
+        ALT0: [AdcChannel<ADC1>(6)],
+        ALT2: [SpiSck<SPI1>],
+        ALT3: [UartCts<UART1>],
+    ],
+    gpioe::PTE3: [
+        ALT0: [AdcChannel<ADC1>(7)],
+        ALT2: [SpiSin<SPI1>],
+        ALT3: [UartRts<UART1>],
+        ALT7: [SpiSout<SPI1>],
+    ],
+    gpioe::PTE4: [
+        ALT2: [SpiPcs<SPI1>(0)],
+        ALT3: [UartTx<UART3>],
+    ],
+    gpioe::PTE5: [
+        ALT2: [SpiPcs<SPI1>(2)],
+        ALT3: [UartRx<UART3>],
+    ],
+    gpioe::PTE6: [
+        ALT2: [SpiPcs<SPI1>(3)],
+        ALT3: [UartCts<UART3>],
+        ALT4: [I2sMclk<I2S0>],
+        ALT6: [UsbSofOut<USB0>],
+    ],
+    gpioe::PTE24: [
+        ALT0: [AdcChannel<ADC0>(17)],
+        ALT3: [UartTx<UART4>],
+        ALT6: [], // EWM_OUT_b
+    ],
+    gpioe::PTE25: [
+        ALT0: [AdcChannel<ADC0>(18)],
+        ALT3: [UartRx<UART4>],
+        ALT6: [], // EWM_IN
+    ],
+    gpioe::PTE26: [
+        ALT3: [UartCts<UART4>],
+        ALT6: [], // RTC_CLKOUT
+        ALT7: [UsbClkin<USB0>],
+    ],
+}
+
+// 121 MAPBGA
+#[cfg(feature = "121mapbga")]
+pins! {
+    gpioa::PTA6: [
+        ALT3: [FtmChannel<FTM0>(3)],
+        ALT7: [], // TRACE_CLKOUT
+    ],
+    gpioa::PTA7: [
+        ALT0: [AdcChannel<ADC0>(10)],
+        ALT3: [FtmChannel<FTM0>(4)],
+        ALT7: [], // TRACE_D3
+    ],
+    gpioa::PTA8: [
+        ALT0: [AdcChannel<ADC0>(11)],
+        ALT3: [FtmChannel<FTM1>(0)],
+        ALT6: [FtmQdPha<FTM1>],
+        ALT7: [], // TRACE_D2
+    ],
+    gpioa::PTA9: [
+        ALT3: [FtmChannel<FTM1>(1)],
+        ALT6: [FtmQdPhb<FTM1>],
+        ALT7: [], // TRACE_D1
+    ],
+    gpioa::PTA10: [
+        ALT3: [FtmChannel<FTM2>(0)],
+        ALT6: [FtmQdPha<FTM2>],
+        ALT7: [], // TRACE_D0
+    ],
+    gpioa::PTA11: [
+        ALT3: [FtmChannel<FTM2>(1)],
+        ALT6: [FtmQdPhb<FTM2>],
+    ],
+    gpiob::PTB4: [
+        ALT0: [AdcChannel<ADC1>(10)],
+        ALT6: [FtmFault<FTM1>(0)],
+    ],
+    gpiob::PTB5: [
+        ALT0: [AdcChannel<ADC1>(11)],
+        ALT6: [FtmFault<FTM2>(0)],
+    ],
+    gpiob::PTB6: [
+        ALT0: [AdcChannel<ADC1>(12)],
+        ALT5: [], // FB_AD23
+    ],
+    gpiob::PTB7: [
+        ALT0: [AdcChannel<ADC1>(13)],
+        ALT5: [], // FB_AD22
+    ],
+    gpiob::PTB8: [
+        ALT3: [UartRts<UART3>],
+        ALT5: [], // FB_AD21
+    ],
+    gpioc::PTC19: [
+        ALT3: [UartCts<UART3>],
+        ALT5: [], // FB_CS3_b
+    ],
+    gpiod::PTD8: [
+        ALT2: [I2cScl<I2C0>],
+        ALT3: [], // UART5_RX
+        ALT6: [], // FB_A16
+    ],
+    gpiod::PTD9: [
+        ALT2: [I2cSda<I2C0>],
+        ALT3: [], // UART5_TX
+        ALT6: [], // FB_A17
+    ],
+    gpiod::PTD10: [
+        ALT3: [], // UART5_RTS_b
+        ALT6: [], // FB_A18
+    ],
+    gpiod::PTD11: [
+        ALT2: [], // SPI2_PCS0
+        ALT3: [], // UART5_CTS_b
+        ALT6: [], // FB_A19
+    ],
+    gpiod::PTD12: [
+        ALT2: [], // SPI2_SCK
+        ALT6: [], // FB_A20
+    ],
+    gpiod::PTD13: [
+        ALT2: [], // SPI2_SOUT
+        ALT6: [], // FB_A21
+    ],
+    gpiod::PTD14: [
+        ALT2: [], // SPI2_SIN
+        ALT6: [], // FB_A22
+    ],
+    gpiod::PTD15: [
+        ALT2: [], // SPI2_PCS1
+        ALT6: [], // FB_A23
+    ],
+}
+
+// 144 LQFP
+#[cfg(feature = "144lqfp")]
+pins! {
+    gpioa::PTA24: [
+        ALT5: [], // FB_A29
+    ],
+    gpioa::PTA25: [
+        ALT5: [], // FB_A28
+    ],
+    gpioa::PTA26: [
+        ALT5: [], // FB_A27
+    ],
+    gpioa::PTA27: [
+        ALT5: [], // FB_A26
+    ],
+    gpioa::PTA28: [
+        ALT5: [], // FB_A25
+    ],
+    gpioa::PTA29: [
+        ALT5: [], // FB_A24
+    ],
+    gpioe::PTE7: [
+        ALT3: [UartRts<UART3>],
+        ALT4: [I2sRxd<I2S0>(0)],
+    ],
+    gpioe::PTE8: [
+        ALT3: [], // UART5_TX
+        ALT4: [I2sRxFs<I2S0>],
+    ],
+    gpioe::PTE9: [
+        ALT3: [], // UART5_RX
+        ALT4: [I2sRxBclk<I2S0>],
+    ],
+    gpioe::PTE10: [
+        ALT3: [], // UART5_CTS_b
+        ALT4: [I2sTxd<I2S0>(0)],
+    ],
+    gpioe::PTE11: [
+        ALT3: [], // UART5_RTS_b
+        ALT4: [I2sTxFs<I2S0>],
+    ],
+    gpioe::PTE12: [
+        ALT4: [I2sTxBclk<I2S0>],
+    ],
+    gpioe::PTE27: [
+        ALT3: [UartRts<UART4>],
+    ],
+    gpioe::PTE28: [],
+}
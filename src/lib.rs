@@ -11,11 +11,15 @@ extern crate bit_field;
 extern crate void;
 extern crate nb;
 
+pub mod bitband;
 pub mod delay;
+pub mod dma;
 pub mod gpio;
 pub mod mcg;
 pub mod osc;
 pub mod prelude;
 pub mod serial;
 pub mod sim;
+#[cfg(feature = "teensy3")]
+pub mod teensy;
 pub mod wdog;
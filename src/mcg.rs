@@ -3,11 +3,14 @@ use core::convert::TryFrom;
 use mk20d7::{mcg::RegisterBlock, mcg::c1};
 
 use sim::MAXIMUM_CLOCK_FREQUENCY;
-use bitrate::{U32BitrateExt, KiloHertz, MegaHertz};
+use bitrate::{U32BitrateExt, Hertz, KiloHertz, MegaHertz};
 
 pub const FLL_RANGE_MIN: f32 = 31.25;
 pub const FLL_RANGE_MAX: f32 = 39.0625;
 
+pub const SLOW_INTERNAL_REFERENCE_FREQUENCY: u32 = 32_768;
+pub const FAST_INTERNAL_REFERENCE_FREQUENCY: u32 = 4_000_000;
+
 pub const PLL_DIVIDER_NUMERATOR_MIN: u8 = 24;
 pub const PLL_DIVIDER_NUMERATOR_MAX: u8 = 55;
 pub const PLL_DIVIDER_DENOMINATOR_MIN: u8 = 1;
@@ -18,27 +21,27 @@ pub struct MultipurposeClockGenerator<'a> {
     pub external_crystal_frequency: MegaHertz<u32>,
 }
 
-pub struct Fei<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-#[allow(dead_code)] pub struct Fee<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-#[allow(dead_code)] pub struct Fbi<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-pub struct Fbe<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-pub struct Pee<'a> { #[allow(dead_code)] mcg: &'a mut MultipurposeClockGenerator<'a> }
-pub struct Pbe<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-#[allow(dead_code)] pub struct Blpi<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-#[allow(dead_code)] pub struct Blpe<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-#[allow(dead_code)] pub struct Stop<'a> { mcg: &'a mut MultipurposeClockGenerator<'a> }
-
-// Multipurpose Clock Generator (MCG) modes of operation
-pub enum ClockMode<'a> {
-    Fei(Fei<'a>), // FEI: Frequency Locked Loop (FLL) Engaged Internal
-    Fee(Fee<'a>), // FEE: Frequency Locked Loop (FLL) Engaged External
-    Fbi(Fbi<'a>), // FBI: Frequency Locked Loop (FLL) Bypassed Internal
-    Fbe(Fbe<'a>), // FBE: Frequency Locked Loop (FLL) Bypassed External
-    Pee(Pee<'a>), // PEE: Phase Locked Loop (PLL) Engaged External
-    Pbe(Pbe<'a>), // PBE: Phase Locked Loop (PLL) Bypassed External
-    Blpi(Blpi<'a>), // BLPI: Bypassed Low Power Internal
-    Blpe(Blpe<'a>), // BLPE: Bypassed Low Power External
-    Stop(Stop<'a>), // Stop
+pub struct Fei<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+#[allow(dead_code)] pub struct Fee<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+#[allow(dead_code)] pub struct Fbi<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+pub struct Fbe<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+pub struct Pee<'b, 'a: 'b> { #[allow(dead_code)] mcg: &'b mut MultipurposeClockGenerator<'a> }
+pub struct Pbe<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+#[allow(dead_code)] pub struct Blpi<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+#[allow(dead_code)] pub struct Blpe<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+#[allow(dead_code)] pub struct Stop<'b, 'a: 'b> { mcg: &'b mut MultipurposeClockGenerator<'a> }
+
+// Multipurpose Clock Generator (MCG) modes of operation, borrowing the MCG for 'b
+pub enum ClockMode<'b, 'a: 'b> {
+    Fei(Fei<'b, 'a>), // FEI: Frequency Locked Loop (FLL) Engaged Internal
+    Fee(Fee<'b, 'a>), // FEE: Frequency Locked Loop (FLL) Engaged External
+    Fbi(Fbi<'b, 'a>), // FBI: Frequency Locked Loop (FLL) Bypassed Internal
+    Fbe(Fbe<'b, 'a>), // FBE: Frequency Locked Loop (FLL) Bypassed External
+    Pee(Pee<'b, 'a>), // PEE: Phase Locked Loop (PLL) Engaged External
+    Pbe(Pbe<'b, 'a>), // PBE: Phase Locked Loop (PLL) Bypassed External
+    Blpi(Blpi<'b, 'a>), // BLPI: Bypassed Low Power Internal
+    Blpe(Blpe<'b, 'a>), // BLPE: Bypassed Low Power External
+    Stop(Stop<'b, 'a>), // Stop
 }
 
 impl<'a> MultipurposeClockGenerator<'a> {
@@ -46,7 +49,7 @@ impl<'a> MultipurposeClockGenerator<'a> {
         MultipurposeClockGenerator { mcg, external_crystal_frequency }
     }
 
-    pub fn clock_mode(&'a mut self) -> ClockMode<'a> {
+    pub fn clock_mode<'b>(&'b mut self) -> ClockMode<'b, 'a> {
         let clock_source = self.mcg.c1.read().clks();
         let internal_clock_reference = self.mcg.c1.read().irefs().bit_is_set();
         let pll_enabled = self.mcg.c6.read().plls().bit_is_set();
@@ -76,22 +79,22 @@ impl<'a> MultipurposeClockGenerator<'a> {
 
     pub fn enable_external_crystal_request(&mut self) {
         if self.external_crystal_is_requested() { return; }
-        self.mcg.c2.write(|w| w.erefs0().set_bit());
+        self.mcg.c2.modify(|_, w| w.erefs0().set_bit());
         while self.mcg.s.read().oscinit0().bit_is_clear() {} // Wait to become enabled
     }
 
     pub fn disable_external_crystal_request(&mut self) {
         if !self.external_crystal_is_requested() { return; }
-        self.mcg.c2.write(|w| w.erefs0().clear_bit());
+        self.mcg.c2.modify(|_, w| w.erefs0().clear_bit());
         while self.mcg.s.read().oscinit0().bit_is_set() {} // Wait to become disabled
     }
 
     pub fn set_external_crystal_frequency_range_low(&mut self) {
-        self.mcg.c2.write(|w| w.range0()._00());
+        self.mcg.c2.modify(|_, w| w.range0()._00());
     }
 
     pub fn set_external_crystal_frequency_range_high(&mut self) {
-        self.mcg.c2.write(|w| w.range0()._01());
+        self.mcg.c2.modify(|_, w| w.range0()._01());
     }
 
     pub fn set_external_crystal_frequency_divider(&self, divider: u16) {
@@ -99,8 +102,8 @@ impl<'a> MultipurposeClockGenerator<'a> {
         let real_time_clock = self.mcg.c7.read().oscsel().bit_is_set();
         let rtc_or_low_freq_crystal = crystal_low_frequency || real_time_clock;
 
-        self.mcg.c1.write(
-            |w| {
+        self.mcg.c1.modify(
+            |_, w| {
                 let frdiv_w = w.frdiv();
                 match divider {
                     _ if rtc_or_low_freq_crystal && divider == 1 || divider == 32 => frdiv_w._000(),
@@ -135,8 +138,8 @@ impl<'a> MultipurposeClockGenerator<'a> {
     }
 
     pub fn use_external_crystal(&mut self) {
-        self.mcg.c1.write(
-            |w| {
+        self.mcg.c1.modify(
+            |_, w| {
                 w.clks()._10();
                 w.irefs().clear_bit()
             }
@@ -157,8 +160,8 @@ impl<'a> MultipurposeClockGenerator<'a> {
             panic!("Invalid PLL reference divide factor: {}", denominator);
         }
 
-        self.mcg.c5.write(|w| unsafe { w.prdiv0().bits(denominator - PLL_DIVIDER_DENOMINATOR_MIN) });
-        self.mcg.c6.write(|w| unsafe { w.vdiv0().bits(numerator - PLL_DIVIDER_NUMERATOR_MIN) });
+        self.mcg.c5.modify(|_, w| unsafe { w.prdiv0().bits(denominator - PLL_DIVIDER_DENOMINATOR_MIN) });
+        self.mcg.c6.modify(|_, w| unsafe { w.vdiv0().bits(numerator - PLL_DIVIDER_NUMERATOR_MIN) });
     }
 
     pub fn get_pll_frequency_divider(&self) -> (u8, u8) {
@@ -182,25 +185,76 @@ impl<'a> MultipurposeClockGenerator<'a> {
         ((num * self.external_crystal_frequency.0) / den).mhz()
     }
 
+    // Reference: 24.4.1.1 Frequency-Locked Loop (FLL)
+    pub fn get_fll_frequency(&self) -> Hertz<u32> {
+        let reference = if self.mcg.c1.read().irefs().bit_is_set() {
+            SLOW_INTERNAL_REFERENCE_FREQUENCY
+        } else {
+            let external_crystal_frequency: Hertz<u32> = self.external_crystal_frequency.into();
+            external_crystal_frequency.0 / u32::from(self.get_external_crystal_frequency_divider())
+        };
+
+        let c4 = self.mcg.c4.read();
+        let factor = match (c4.dmx32().bit_is_set(), c4.drst_drs().bits()) {
+            (false, 0) => 640,
+            (false, 1) => 1280,
+            (false, 2) => 1920,
+            (false, _) => 2560,
+            (true, 0) => 732,
+            (true, 1) => 1464,
+            (true, 2) => 2197,
+            (true, _) => 2929,
+        };
+
+        (reference * factor).hz()
+    }
+
+    pub fn get_internal_reference_frequency(&self) -> Hertz<u32> {
+        if self.mcg.c2.read().ircs().bit_is_set() {
+            (FAST_INTERNAL_REFERENCE_FREQUENCY >> self.mcg.sc.read().fcrdiv().bits()).hz()
+        } else {
+            SLOW_INTERNAL_REFERENCE_FREQUENCY.hz()
+        }
+    }
+
+    pub fn get_pll_output_frequency(&self) -> Hertz<u32> {
+        let (numerator, denominator) = self.get_pll_frequency_divider();
+        let external_crystal_frequency: Hertz<u32> = self.external_crystal_frequency.into();
+        let frequency = u64::from(external_crystal_frequency.0) * u64::from(numerator) / u64::from(denominator);
+        (frequency as u32).hz()
+    }
+
+    // Reference: 24.4.1 MCG mode state diagram
+    pub fn get_output_frequency(&self) -> Hertz<u32> {
+        output_frequency(
+            self.mcg.s.read().clkst().bits(),
+            self.get_fll_frequency(),
+            self.get_internal_reference_frequency(),
+            self.external_crystal_frequency.into(),
+            self.get_pll_output_frequency(),
+        )
+    }
+
     pub fn enable_pll(&mut self) {
-        self.mcg.c6.write(|w| w.plls().set_bit());
+        self.mcg.c6.modify(|_, w| w.plls().set_bit());
         while self.mcg.s.read().pllst().bit_is_clear() {} // Wait for PLL to be enabled
         while self.mcg.s.read().lock0().bit_is_clear() {} // Wait for PLL to be "locked" and stable
     }
 
     pub fn use_pll(&mut self) {
-        self.mcg.c1.write(|w| w.clks()._10());
+        while self.mcg.s.read().lock0().bit_is_clear() {} // The PLL must be locked before it is selected
+        self.mcg.c1.modify(|_, w| w.clks()._00());
 
         // mcg.c1 and mcg.s have slightly different behaviors. In c1, we use one value to indicate
         // "Use whichever LL is enabled". In s, it is differentiated between the FLL at 0, and the
         // PLL at 3. Instead of adding a value to OscSource which would be invalid to set, we just
         // check for the known value "3" here.
-        while !self.mcg.s.read().clkst().is_10() {}
+        while !self.mcg.s.read().clkst().is_11() {}
     }
 }
 
-impl<'a> Into<Fbe<'a>> for Fei<'a> {
-    fn into(self) -> Fbe<'a> {
+impl<'b, 'a> Into<Fbe<'b, 'a>> for Fei<'b, 'a> {
+    fn into(self) -> Fbe<'b, 'a> {
         self.mcg.set_external_crystal_frequency_range_high();
         self.mcg.enable_external_crystal_request();
         self.mcg.set_external_crystal_frequency_divider(512); // FIXME: Assumes a 16 Mhz crystal, don't hard code this
@@ -212,8 +266,8 @@ impl<'a> Into<Fbe<'a>> for Fei<'a> {
     }
 }
 
-impl<'a> Into<Pbe<'a>> for Fbe<'a> {
-    fn into(self) -> Pbe<'a> {
+impl<'b, 'a> Into<Pbe<'b, 'a>> for Fbe<'b, 'a> {
+    fn into(self) -> Pbe<'b, 'a> {
         self.mcg.set_pll_frequency(u32::from(MAXIMUM_CLOCK_FREQUENCY).mhz()); // FIXME: Assumes 72 Mhz, don't hard code this
         self.mcg.enable_pll();
         match self.mcg.clock_mode() {
@@ -223,8 +277,8 @@ impl<'a> Into<Pbe<'a>> for Fbe<'a> {
     }
 }
 
-impl<'a> Into<Pee<'a>> for Pbe<'a> {
-    fn into(self) -> Pee<'a> {
+impl<'b, 'a> Into<Pee<'b, 'a>> for Pbe<'b, 'a> {
+    fn into(self) -> Pee<'b, 'a> {
         self.mcg.use_pll();
         match self.mcg.clock_mode() {
             ClockMode::Pee(pee) => pee,
@@ -233,6 +287,23 @@ impl<'a> Into<Pee<'a>> for Pbe<'a> {
     }
 }
 
+// Selects the MCG output frequency from the clock mode status CLKST
+// Reference: 24.3.7 MCG Status Register (MCG_S)
+fn output_frequency(
+    clock_mode_status: u8,
+    fll: Hertz<u32>,
+    internal_reference: Hertz<u32>,
+    external_reference: Hertz<u32>,
+    pll: Hertz<u32>,
+) -> Hertz<u32> {
+    match clock_mode_status & 0b11 {
+        0b00 => fll,
+        0b01 => internal_reference,
+        0b10 => external_reference,
+        _ => pll,
+    }
+}
+
 fn pll_frequency_divider_gcd(numerator: u8, denominator: u8) -> (u8, u8) {
     // Euclid's GCD
     let mut num = numerator;
@@ -270,3 +341,34 @@ fn pll_frequency_divider_gcd(numerator: u8, denominator: u8) -> (u8, u8) {
 
     (freq_num, freq_den)
 }
+
+#[cfg(test)]
+mod tests {
+    use bitrate::U32BitrateExt;
+
+    use super::output_frequency;
+
+    fn output_frequency_of(clock_mode_status: u8) -> u32 {
+        output_frequency(clock_mode_status, 20.mhz().into(), 4.mhz().into(), 16.mhz().into(), 72.mhz().into()).0
+    }
+
+    #[test]
+    fn output_frequency_of_fll() {
+        assert_eq!(output_frequency_of(0b00), 20_000_000);
+    }
+
+    #[test]
+    fn output_frequency_of_internal_reference() {
+        assert_eq!(output_frequency_of(0b01), 4_000_000);
+    }
+
+    #[test]
+    fn output_frequency_of_external_reference() {
+        assert_eq!(output_frequency_of(0b10), 16_000_000);
+    }
+
+    #[test]
+    fn output_frequency_of_pll() {
+        assert_eq!(output_frequency_of(0b11), 72_000_000);
+    }
+}
@@ -1,34 +1,353 @@
 use core::marker::PhantomData;
-use core::convert::TryFrom;
 
 use nb;
 use bitrate::{Bps, Hertz};
 use bit_field::BitField;
 use hal::serial;
-use mk20d7::{self, UART0, UART1, UART2};
-use void::Void;
-
-use mcg::MultipurposeClockGenerator;
-use gpio::{
-    Alternate, ALT2, ALT3,
-    gpioa::{PTA1, PTA2},
-    gpiob::{PTB16, PTB17},
-    gpioc::{PTC3, PTC4},
-    gpiod::{PTD2, PTD3, PTD6, PTD7},
-    gpioe::{PTE0, PTE1},
-};
+use mk20d7::{self, sim::SOPT5, UART0, UART1, UART2};
+#[cfg(feature = "100lqfp")]
+use mk20d7::{UART3, UART4};
+
+use bitband;
+use dma::{self, Channel, CircularTransfer, Payload, Transfer};
+use sim::Clocks;
+use gpio::private::Sealed;
+use gpio::PinLevel;
+use gpio::signals::{UartCts, UartRts, UartRx, UartTx};
+
+pub mod buffered;
+pub mod lin;
+pub mod smartcard;
+
+/// Largest baud rate error accepted by the constructors, in thousandths of the requested rate
+pub const BAUD_RATE_TOLERANCE: u32 = 25;
+
+/// Largest value of the SBR field of BDH and BDL
+const MODULE_CLOCK_DIVISOR_MAX: u32 = 8191;
+
+/// Interrupt event of the receiver
+#[derive(Clone, Copy, PartialEq)]
+pub enum RxEvent {
+    /// New data has been received
+    Rxne,
+
+    /// Receiver line idle
+    Idle,
+
+    /// RX buffer overrun
+    Overrun,
+
+    /// Noise detected
+    Noise,
+
+    /// Framing error
+    Framing,
+
+    /// Parity check error
+    Parity,
+
+    /// LIN break detected
+    LinBreak,
+}
+
+/// Interrupt event of the transmitter
+#[derive(Clone, Copy, PartialEq)]
+pub enum TxEvent {
+    /// New data can be sent
+    Txe,
+
+    /// Transmission complete
+    Tc,
+}
 
 /// Interrupt event
+#[derive(Clone, Copy, PartialEq)]
 pub enum Event {
     /// New data has been received
     Rxne,
 
     /// New data can be sent
     Txe,
+
+    /// Transmission complete
+    Tc,
+
+    /// Receiver line idle
+    Idle,
+
+    /// RX buffer overrun
+    Overrun,
+
+    /// Noise detected
+    Noise,
+
+    /// Framing error
+    Framing,
+
+    /// Parity check error
+    Parity,
+
+    /// LIN break detected
+    LinBreak,
+}
+
+impl From<RxEvent> for Event {
+    fn from(event: RxEvent) -> Event {
+        match event {
+            RxEvent::Rxne => Event::Rxne,
+            RxEvent::Idle => Event::Idle,
+            RxEvent::Overrun => Event::Overrun,
+            RxEvent::Noise => Event::Noise,
+            RxEvent::Framing => Event::Framing,
+            RxEvent::Parity => Event::Parity,
+            RxEvent::LinBreak => Event::LinBreak,
+        }
+    }
+}
+
+impl From<TxEvent> for Event {
+    fn from(event: TxEvent) -> Event {
+        match event {
+            TxEvent::Txe => Event::Txe,
+            TxEvent::Tc => Event::Tc,
+        }
+    }
+}
+
+/// Number of data bits of a frame
+#[derive(Clone, Copy, PartialEq)]
+pub enum WordLength {
+    /// 8 data bits
+    DataBits8,
+
+    /// 9 data bits, read and written as `u16`
+    DataBits9,
+}
+
+/// Parity bit of a frame
+#[derive(Clone, Copy, PartialEq)]
+pub enum Parity {
+    /// No parity bit
+    ParityNone,
+
+    /// Even parity
+    ParityEven,
+
+    /// Odd parity
+    ParityOdd,
+}
+
+/// Number of stop bits of a frame
+#[derive(Clone, Copy, PartialEq)]
+pub enum StopBits {
+    /// 1 stop bit
+    STOP1,
+
+    /// 2 stop bits
+    ///
+    /// The UARTs of this device have no stop bit number select, so the second stop bit is sent as
+    /// a ninth data bit that is always set. It is only available with 8 data bits and no parity,
+    /// other frames are rejected with `Error::InvalidFrame`.
+    STOP2,
+}
+
+/// Order in which the data bits of a frame are sent
+#[derive(Clone, Copy, PartialEq)]
+pub enum BitOrder {
+    /// Least significant bit first
+    LsbFirst,
+
+    /// Most significant bit first
+    MsbFirst,
+}
+
+/// Condition waking the receiver up from standby
+#[derive(Clone, Copy, PartialEq)]
+pub enum Wakeup {
+    /// An idle line wakes the receiver up
+    IdleLine,
+
+    /// A frame with its most significant data bit set, an address mark, wakes the receiver up
+    AddressMark,
+}
+
+/// Start of the count of the idle characters detecting an idle line
+#[derive(Clone, Copy, PartialEq)]
+pub enum IdleLineType {
+    /// The count starts after the start bit, so the stop bits of a frame count as idle
+    AfterStartBit,
+
+    /// The count starts after the stop bit
+    AfterStopBit,
+}
+
+/// Serial configuration
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Baud rate
+    pub baud_rate: Bps<u32>,
+
+    /// Number of data bits
+    pub word_length: WordLength,
+
+    /// Parity bit
+    pub parity: Parity,
+
+    /// Number of stop bits
+    pub stop_bits: StopBits,
+
+    /// Bit order
+    pub bit_order: BitOrder,
+
+    /// Inverts the polarity of the received data
+    pub invert_rx: bool,
+
+    /// Inverts the polarity of the transmitted data
+    pub invert_tx: bool,
+
+    /// Condition waking the receiver up from standby
+    pub wakeup: Wakeup,
+
+    /// Start of the idle line detection
+    pub idle_line_type: IdleLineType,
+
+    /// Address received by the node, compared to the frames with their most significant data bit
+    /// set
+    ///
+    /// While address matching is enabled, every frame but a matching address is discarded,
+    /// including the data following the address. Once the address is received, call
+    /// `Rx::disable_address_match` to read the data of the message, then
+    /// `Rx::enable_address_match` at its end to wait for the next address.
+    pub match_address1: Option<u8>,
+
+    /// Second address received by the node
+    pub match_address2: Option<u8>,
+
+    /// Sends 13 bit break characters and detects 11 bit breaks, as LIN does
+    pub lin_break: bool,
+}
+
+impl Config {
+    /// Sets the baud rate
+    pub fn baud_rate(mut self, baud_rate: Bps<u32>) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    /// Uses 8 data bits
+    pub fn word_length_8(mut self) -> Self {
+        self.word_length = WordLength::DataBits8;
+        self
+    }
+
+    /// Uses 9 data bits
+    pub fn word_length_9(mut self) -> Self {
+        self.word_length = WordLength::DataBits9;
+        self
+    }
+
+    /// Disables the parity bit
+    pub fn parity_none(mut self) -> Self {
+        self.parity = Parity::ParityNone;
+        self
+    }
+
+    /// Uses even parity
+    pub fn parity_even(mut self) -> Self {
+        self.parity = Parity::ParityEven;
+        self
+    }
+
+    /// Uses odd parity
+    pub fn parity_odd(mut self) -> Self {
+        self.parity = Parity::ParityOdd;
+        self
+    }
+
+    /// Sets the number of stop bits
+    pub fn stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+
+    /// Sends the most significant bit first
+    pub fn msb_first(mut self) -> Self {
+        self.bit_order = BitOrder::MsbFirst;
+        self
+    }
+
+    /// Sends the least significant bit first
+    pub fn lsb_first(mut self) -> Self {
+        self.bit_order = BitOrder::LsbFirst;
+        self
+    }
+
+    /// Inverts the polarity of the received data
+    pub fn invert_rx(mut self, invert: bool) -> Self {
+        self.invert_rx = invert;
+        self
+    }
+
+    /// Inverts the polarity of the transmitted data
+    pub fn invert_tx(mut self, invert: bool) -> Self {
+        self.invert_tx = invert;
+        self
+    }
+
+    /// Sets the condition waking the receiver up from standby
+    pub fn wakeup(mut self, wakeup: Wakeup) -> Self {
+        self.wakeup = wakeup;
+        self
+    }
+
+    /// Sets the start of the idle line detection
+    pub fn idle_line_type(mut self, idle_line_type: IdleLineType) -> Self {
+        self.idle_line_type = idle_line_type;
+        self
+    }
+
+    /// Sets the first address received by the node
+    pub fn match_address1(mut self, address: u8) -> Self {
+        self.match_address1 = Some(address);
+        self
+    }
+
+    /// Sets the second address received by the node
+    pub fn match_address2(mut self, address: u8) -> Self {
+        self.match_address2 = Some(address);
+        self
+    }
+
+    /// Sends 13 bit break characters and detects 11 bit breaks, as LIN does
+    pub fn lin_break(mut self, enabled: bool) -> Self {
+        self.lin_break = enabled;
+        self
+    }
+}
+
+impl Default for Config {
+    /// 115200 baud, 8 data bits, no parity, 1 stop bit, LSB first, no inversion, idle line
+    /// wake-up, no address matching, no LIN break
+    fn default() -> Config {
+        Config {
+            baud_rate: Bps(115_200),
+            word_length: WordLength::DataBits8,
+            parity: Parity::ParityNone,
+            stop_bits: StopBits::STOP1,
+            bit_order: BitOrder::LsbFirst,
+            invert_rx: false,
+            invert_tx: false,
+            wakeup: Wakeup::IdleLine,
+            idle_line_type: IdleLineType::AfterStartBit,
+            match_address1: None,
+            match_address2: None,
+            lin_break: false,
+        }
+    }
 }
 
 /// Serial error
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Error {
     /// Framing error
     Framing,
@@ -42,47 +361,103 @@ pub enum Error {
     /// Parity check error
     Parity,
 
+    /// TX FIFO overflow
+    FifoOverflow,
+
+    /// RX FIFO underflow
+    FifoUnderflow,
+
+    /// The baud rate cannot be generated from the module clock within `BAUD_RATE_TOLERANCE`
+    BaudRate,
+
+    /// 2 stop bits are only supported with 8 data bits and no parity
+    InvalidFrame,
+
     #[doc(hidden)]
     _Extensible,
 }
 
-// FIXME these should be a "sealed" trait
-/// TX pin - DO NOT IMPLEMENT THIS TRAIT
-pub unsafe trait TxPin<UART> {}
+/// Width of the pulses of the IrDA encoder, in bit times
+#[derive(Clone, Copy, PartialEq)]
+pub enum IrdaPulseWidth {
+    /// 3/16 of a bit, as in the IrDA SIR standard
+    ThreeSixteenths,
+
+    /// 1/16 of a bit
+    OneSixteenth,
 
-// FIXME these should be a "sealed" trait
-/// RX pin - DO NOT IMPLEMENT THIS TRAIT
-pub unsafe trait RxPin<UART> {}
+    /// 1/32 of a bit
+    OneThirtySecond,
 
-// UART 0 PTA
-unsafe impl RxPin<UART0> for PTA1<Alternate<ALT2>> {}
-unsafe impl TxPin<UART0> for PTA2<Alternate<ALT2>> {}
+    /// 1/4 of a bit
+    OneQuarter,
+}
+
+/// Signal driving the UART0 TX pin
+#[derive(Clone, Copy, PartialEq)]
+pub enum TxModulation {
+    /// The UART0 TX signal itself
+    None,
 
-// UART 0 PTB
-unsafe impl RxPin<UART0> for PTB17<Alternate<ALT3>> {}
-unsafe impl TxPin<UART0> for PTB16<Alternate<ALT3>> {}
+    /// The UART0 TX signal modulated with the output of FTM1 channel 0
+    Ftm1Channel0,
+
+    /// The UART0 TX signal modulated with the output of FTM2 channel 0
+    Ftm2Channel0,
+}
 
-// UART 0 PTD
-unsafe impl RxPin<UART0> for PTD6<Alternate<ALT3>> {}
-unsafe impl TxPin<UART0> for PTD7<Alternate<ALT3>> {}
+/// TX pin of a UART
+pub trait TxPin<UART>: Sealed {}
 
-// UART 1 PTC
-unsafe impl RxPin<UART1> for PTC3<Alternate<ALT3>> {}
-unsafe impl TxPin<UART1> for PTC4<Alternate<ALT3>> {}
+/// RX pin of a UART
+pub trait RxPin<UART>: Sealed {}
 
-// UART 1 PTE
-unsafe impl RxPin<UART1> for PTE1<Alternate<ALT3>> {}
-unsafe impl TxPin<UART1> for PTE0<Alternate<ALT3>> {}
+/// CTS pin of a UART
+pub trait CtsPin<UART>: Sealed {}
 
-// UART 2 PTD
-unsafe impl RxPin<UART1> for PTD2<Alternate<ALT3>> {}
-unsafe impl TxPin<UART1> for PTD3<Alternate<ALT3>> {}
+/// RTS pin of a UART
+pub trait RtsPin<UART>: Sealed {}
 
+// The pins of each UART are listed in the signal multiplexing table of `gpio::signals`
+impl<UART, PIN> TxPin<UART> for PIN where PIN: UartTx<UART> {}
+impl<UART, PIN> RxPin<UART> for PIN where PIN: UartRx<UART> {}
+impl<UART, PIN> CtsPin<UART> for PIN where PIN: UartCts<UART> {}
+impl<UART, PIN> RtsPin<UART> for PIN where PIN: UartRts<UART> {}
 
 /// Serial abstraction
 pub struct Serial<UART, PINS> {
     uart: UART,
     pins: PINS,
+    baud_rate: Bps<u32>,
+    cts: Option<fn() -> bool>,
+    two_stop_bits: bool,
+}
+
+/// Half-duplex serial interface over the single wire of the TX pin
+pub struct HalfDuplex<UART, PIN> {
+    uart: UART,
+    pin: PIN,
+    baud_rate: Bps<u32>,
+}
+
+/// Direction of a half-duplex serial interface
+#[derive(Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// The TX pin listens to the wire
+    Receive,
+
+    /// The TX pin drives the wire
+    Transmit,
+}
+
+/// Level of the RTS pin enabling the driver of an RS-485 transceiver
+#[derive(Clone, Copy, PartialEq)]
+pub enum DriverEnablePolarity {
+    /// RTS is low while transmitting
+    ActiveLow,
+
+    /// RTS is high while transmitting
+    ActiveHigh,
 }
 
 /// Serial receiver
@@ -93,76 +468,328 @@ pub struct Rx<UART> {
 /// Serial transmitter
 pub struct Tx<UART> {
     _uart: PhantomData<UART>,
+    cts: Option<fn() -> bool>,
+    two_stop_bits: bool,
+}
+
+impl<UART> Tx<UART> {
+    // Transmitter without flow control, for the interfaces built on top of a UART
+    fn new() -> Self {
+        Tx { _uart: PhantomData, cts: None, two_stop_bits: false }
+    }
+
+    /// Returns `false` while the transmitter is held by a deasserted CTS pin
+    ///
+    /// The writes return `WouldBlock` until the peer asserts CTS again.
+    pub fn is_clear_to_send(&self) -> bool {
+        is_clear_to_send(self.cts)
+    }
+}
+
+impl<UART, PINS> Serial<UART, PINS> {
+    /// Returns `false` while the transmitter is held by a deasserted CTS pin
+    pub fn is_clear_to_send(&self) -> bool {
+        is_clear_to_send(self.cts)
+    }
+}
+
+// CTS is active low, the transmitter is held while it is high
+fn is_clear_to_send(cts: Option<fn() -> bool>) -> bool {
+    match cts {
+        Some(is_level_high) => !is_level_high(),
+        None => true,
+    }
+}
+
+// Line mode selected by the constructors, on top of the frame of `Config`
+#[derive(Clone, Copy)]
+enum Mode {
+    Normal,
+    SingleWire,
+    Rs485(DriverEnablePolarity),
+    FlowControl,
+    Irda(IrdaPulseWidth),
 }
 
 macro_rules! hal {
     ($(
-        $UARTX:ident: ($uartX:ident),
+        $UARTX:ident: ($uartX:ident, $uartX_rs485:ident, $uartX_flow_control:ident, $clock:ident),
     )+) => {
         $(
             impl<TX, RX> Serial<$UARTX, (TX, RX)> {
-                // Reference 47.8.3 Initialization sequence (non ISO-7816)
                 /// Configures a UART peripheral to provide serial communication
                 pub fn $uartX(
                     uart: $UARTX,
                     pins: (TX, RX),
-                    baud_rate: Bps<u32>,
-                    mcg: &MultipurposeClockGenerator,
-                ) -> Self
+                    config: Config,
+                    clocks: &Clocks,
+                ) -> Result<Self, Error>
+                where
+                    TX: TxPin<$UARTX>,
+                    RX: RxPin<$UARTX>,
+                {
+                    let baud_rate = Self::configure(&uart, &config, clocks, Mode::Normal)?;
+
+                    let two_stop_bits = config.stop_bits == StopBits::STOP2;
+
+                    Ok(Serial { uart, pins, baud_rate, cts: None, two_stop_bits })
+                }
+            }
+
+            impl<TX, RX, RTS> Serial<$UARTX, (TX, RX, RTS)> {
+                /// Configures a UART peripheral for an RS-485 transceiver, RTS driving the
+                /// transceiver driver enable while a frame is sent
+                pub fn $uartX_rs485(
+                    uart: $UARTX,
+                    pins: (TX, RX, RTS),
+                    config: Config,
+                    clocks: &Clocks,
+                    polarity: DriverEnablePolarity,
+                ) -> Result<Self, Error>
+                where
+                    TX: TxPin<$UARTX>,
+                    RX: RxPin<$UARTX>,
+                    RTS: RtsPin<$UARTX>,
+                {
+                    let baud_rate = Self::configure(&uart, &config, clocks, Mode::Rs485(polarity))?;
+
+                    let two_stop_bits = config.stop_bits == StopBits::STOP2;
+
+                    Ok(Serial { uart, pins, baud_rate, cts: None, two_stop_bits })
+                }
+            }
+
+            impl<TX, RX, CTS, RTS> Serial<$UARTX, (TX, RX, CTS, RTS)> {
+                /// Configures a UART peripheral with hardware flow control: the transmitter waits
+                /// for CTS to be asserted, and RTS is deasserted while the receiver is full
+                pub fn $uartX_flow_control(
+                    uart: $UARTX,
+                    pins: (TX, RX, CTS, RTS),
+                    config: Config,
+                    clocks: &Clocks,
+                ) -> Result<Self, Error>
                 where
                     TX: TxPin<$UARTX>,
                     RX: RxPin<$UARTX>,
+                    CTS: CtsPin<$UARTX> + PinLevel,
+                    RTS: RtsPin<$UARTX>,
                 {
-                    // Reference: 47.4.4 Baud rate generation
-                    let clock: Hertz<u32> = mcg.get_pll_frequency().into();
-                    let numerator = clock.0;
-                    let denominator = baud_rate.0 * mcg.external_crystal_frequency.0;
+                    let baud_rate = Self::configure(&uart, &config, clocks, Mode::FlowControl)?;
+
+                    let two_stop_bits = config.stop_bits == StopBits::STOP2;
 
-                    let module_clock_divisor_main = numerator / denominator;
+                    let cts = Some(CTS::is_level_high as fn() -> bool);
 
-                    if module_clock_divisor_main >= 8192 {
-                        panic!("Invalid UART clock divider: {}", module_clock_divisor_main);
+                    Ok(Serial { uart, pins, baud_rate, cts, two_stop_bits })
+                }
+            }
+
+            impl<PIN> HalfDuplex<$UARTX, PIN> {
+                /// Configures a UART peripheral to communicate over the single wire of its TX pin
+                ///
+                /// The pin starts receiving, see `set_direction`.
+                pub fn $uartX(
+                    uart: $UARTX,
+                    pin: PIN,
+                    config: Config,
+                    clocks: &Clocks,
+                ) -> Result<Self, Error>
+                where
+                    PIN: TxPin<$UARTX>,
+                {
+                    let baud_rate =
+                        Serial::<$UARTX, PIN>::configure(&uart, &config, clocks, Mode::SingleWire)?;
+
+                    Ok(HalfDuplex { uart, pin, baud_rate })
+                }
+
+                /// Returns the baud rate actually generated from the module clock
+                pub fn baud_rate(&self) -> Bps<u32> {
+                    self.baud_rate
+                }
+
+                /// Sets whether the TX pin drives the wire or listens to it
+                pub fn set_direction(&mut self, direction: Direction) {
+                    // Reference: 47.3.7 UART Control Register 3 (UART_C3)
+                    unsafe { bitband::write(self.uart.c3(), 5, direction == Direction::Transmit) };
+                }
+
+                /// Returns whether the TX pin drives the wire or listens to it
+                pub fn direction(&self) -> Direction {
+                    if self.uart.c3.read().txdir().bit_is_set() {
+                        Direction::Transmit
+                    } else {
+                        Direction::Receive
                     }
+                }
 
-                    let module_clock_divisor_gcd = gcd(numerator, denominator);
-                    let module_clock_divisor_fine_adjustment_denominator = {
-                        denominator / module_clock_divisor_gcd
-                    };
-                    let module_clock_divisor_fine_adjustment_numerator = {
-                        (numerator / module_clock_divisor_gcd) - (module_clock_divisor_main * module_clock_divisor_fine_adjustment_denominator)
+                /// Releases the UART peripheral and the pin
+                pub fn free(self) -> ($UARTX, PIN) {
+                    (self.uart, self.pin)
+                }
+            }
+
+            impl<PIN> serial::Read<u8> for HalfDuplex<$UARTX, PIN> {
+                type Error = Error;
+
+                fn read(&mut self) -> nb::Result<u8, Error> {
+                    serial::Read::<u8>::read(&mut Rx::<$UARTX> { _uart: PhantomData })
+                }
+            }
+
+            impl<PIN> serial::Write<u8> for HalfDuplex<$UARTX, PIN> {
+                type Error = Error;
+
+                fn flush(&mut self) -> nb::Result<(), Error> {
+                    serial::Write::<u8>::flush(&mut Tx::<$UARTX>::new())
+                }
+
+                /// Sends a byte, the direction being `Direction::Transmit`
+                fn write(&mut self, byte: u8) -> nb::Result<(), Error> {
+                    serial::Write::<u8>::write(&mut Tx::<$UARTX>::new(), byte)
+                }
+            }
+
+            impl<PINS> Serial<$UARTX, PINS> {
+                // Reference 47.8.3 Initialization sequence (non ISO-7816)
+                fn configure(
+                    uart: &$UARTX,
+                    config: &Config,
+                    clocks: &Clocks,
+                    mode: Mode,
+                ) -> Result<Bps<u32>, Error> {
+                    let (module_clock_divisor, module_clock_divisor_fine_adjustment, baud_rate) =
+                        baud_rate_divisor(clocks.$clock, config.baud_rate)?;
+
+                    let parity = config.parity != Parity::ParityNone;
+                    let nine_data_bits = config.word_length == WordLength::DataBits9;
+                    let two_stop_bits = config.stop_bits == StopBits::STOP2;
+
+                    if two_stop_bits && (parity || nine_data_bits) {
+                        return Err(Error::InvalidFrame);
+                    }
+
+                    let single_wire = match mode {
+                        Mode::SingleWire => true,
+                        _ => false,
                     };
-                    let module_clock_divisor_fine_adjustment = u8::try_from(
-                        module_clock_divisor_fine_adjustment_numerator * 32 /
-                        module_clock_divisor_fine_adjustment_denominator
-                    ).unwrap();
+
+                    // The transmitter and the receiver are disabled while the UART is configured
+                    // Reference: 47.3.4 UART Control Register 2 (UART_C2)
+                    uart.c2.write(|w| w);
+
+                    // Every register is written, so that no setting of a previous mode is kept
+                    // Reference: 47.3.14 UART Modem Register (UART_MODEM)
+                    uart.modem.write(|w| match mode {
+                        Mode::Rs485(polarity) => {
+                            w.txrtspol().bit(polarity == DriverEnablePolarity::ActiveHigh);
+                            w.txrtse().set_bit()
+                        },
+                        Mode::FlowControl => {
+                            w.rxrtse().set_bit();
+                            w.txctse().set_bit()
+                        },
+                        _ => w,
+                    });
+
+                    // Reference: 47.3.15 UART Infrared Register (UART_IR)
+                    uart.ir.write(|w| match mode {
+                        Mode::Irda(pulse_width) => {
+                            match pulse_width {
+                                IrdaPulseWidth::ThreeSixteenths => w.tnp()._00(),
+                                IrdaPulseWidth::OneSixteenth => w.tnp()._01(),
+                                IrdaPulseWidth::OneThirtySecond => w.tnp()._10(),
+                                IrdaPulseWidth::OneQuarter => w.tnp()._11(),
+                            };
+                            w.iren().set_bit()
+                        },
+                        _ => w,
+                    });
 
                     // Reference: 47.3.11 UART Control Register 4 (UART_C4)
-                    uart.c4.write(|w| unsafe { w.brfa().bits(module_clock_divisor_fine_adjustment) });
+                    uart.c4.write(|w| {
+                        w.maen1().bit(config.match_address1.is_some());
+                        w.maen2().bit(config.match_address2.is_some());
+                        w.m10().bit(parity && nine_data_bits);
+                        unsafe { w.brfa().bits(module_clock_divisor_fine_adjustment) }
+                    });
+
+                    // Reference: 47.3.9 UART Match Address Registers 1 (UART_MA1)
+                    uart.ma1.write(|w| unsafe { w.ma().bits(config.match_address1.unwrap_or(0)) });
+
+                    // Reference: 47.3.10 UART Match Address Registers 2 (UART_MA2)
+                    uart.ma2.write(|w| unsafe { w.ma().bits(config.match_address2.unwrap_or(0)) });
 
                     // Reference: 47.3.1 UART Baud Rate Registers: High (UART_BDH)
-                    let module_clock_divisor_high = module_clock_divisor_main.get_bits(8..13) as u8;
+                    let module_clock_divisor_high = module_clock_divisor.get_bits(8..13) as u8;
                     uart.bdh.write(|w| unsafe { w.sbr().bits(module_clock_divisor_high) });
 
                     // Reference: 47.3.2 UART Baud Rate Registers: Low (UART_BDL)
-                    let module_clock_divisor_low = module_clock_divisor_main.get_bits(0..8) as u8;
+                    let module_clock_divisor_low = module_clock_divisor.get_bits(0..8) as u8;
                     uart.bdl.write(|w| unsafe { w.sbr().bits(module_clock_divisor_low) });
 
+                    // Reference: 47.3.3 UART Control Register 1 (UART_C1)
+                    uart.c1.write(|w| {
+                        w.loops().bit(single_wire);
+                        w.rsrc().bit(single_wire);
+                        w.wake().bit(config.wakeup == Wakeup::AddressMark);
+                        w.ilt().bit(config.idle_line_type == IdleLineType::AfterStopBit);
+                        w.m().bit(parity || nine_data_bits || two_stop_bits);
+                        w.pe().bit(parity);
+                        w.pt().bit(config.parity == Parity::ParityOdd)
+                    });
+
+                    // Reference: 47.3.6 UART Status Register 2 (UART_S2)
+                    uart.s2.write(|w| {
+                        w.msbf().bit(config.bit_order == BitOrder::MsbFirst);
+                        w.rxinv().bit(config.invert_rx);
+                        w.brk13().bit(config.lin_break);
+                        w.lbkde().bit(config.lin_break)
+                    });
+
+                    // Reference: 47.3.7 UART Control Register 3 (UART_C3)
+                    uart.c3.write(|w| {
+                        w.t8().bit(two_stop_bits);
+                        w.txinv().bit(config.invert_tx)
+                    });
+
                     // Reference: 47.3.4 UART Control Register 2 (UART_C2)
                     uart.c2.write(|w| {
                         w.re().set_bit();
                         w.te().set_bit()
                     });
 
-                    Serial { uart, pins }
+                    Ok(baud_rate)
+                }
+
+                /// Returns the baud rate actually generated from the module clock
+                pub fn baud_rate(&self) -> Bps<u32> {
+                    self.baud_rate
+                }
+
+                /// Starts listening for an interrupt event
+                pub fn listen(&mut self, event: Event) {
+                    set_event(&*self.uart, event, true);
+                }
+
+                /// Stops listening for an interrupt event
+                pub fn unlisten(&mut self, event: Event) {
+                    set_event(&*self.uart, event, false);
                 }
 
                 /// Splits the `Serial` abstraction into a transmitter and a receiver half
                 pub fn split(self) -> (Tx<$UARTX>, Rx<$UARTX>) {
-                    (Tx { _uart: PhantomData }, Rx { _uart: PhantomData })
+                    let tx = Tx {
+                        _uart: PhantomData,
+                        cts: self.cts,
+                        two_stop_bits: self.two_stop_bits,
+                    };
+
+                    (tx, Rx { _uart: PhantomData })
                 }
 
                 /// Releases the UART peripheral and associated pins
-                pub fn free(self) -> ($UARTX, (TX, RX)) {
+                pub fn free(self) -> ($UARTX, PINS) {
                     (self.uart, self.pins)
                 }
             }
@@ -171,30 +798,164 @@ macro_rules! hal {
                 unsafe { &(*$UARTX::ptr()) }
             }
 
-            impl serial::Read<u8> for Rx<$UARTX> {
-                type Error = Error;
+            impl ControlRegisters for mk20d7::$uartX::RegisterBlock {
+                fn bdh(&self) -> *const u8 {
+                    &self.bdh as *const _ as *const u8
+                }
 
-                fn read(&mut self) -> nb::Result<u8, Error> {
+                fn c2(&self) -> *const u8 {
+                    &self.c2 as *const _ as *const u8
+                }
+
+                fn c3(&self) -> *const u8 {
+                    &self.c3 as *const _ as *const u8
+                }
+
+                fn c5(&self) -> *const u8 {
+                    &self.c5 as *const _ as *const u8
+                }
+            }
+
+            impl Rx<$UARTX> {
+                /// Starts listening for a receiver interrupt event
+                pub fn listen(&mut self, event: RxEvent) {
+                    set_rx_event($uartX(), event, true);
+                }
+
+                /// Stops listening for a receiver interrupt event
+                pub fn unlisten(&mut self, event: RxEvent) {
+                    set_rx_event($uartX(), event, false);
+                }
+
+                /// Receives every frame, e.g. the data following a matched address
+                pub fn disable_address_match(&mut self) {
+                    // Reference: 47.3.11 UART Control Register 4 (UART_C4)
+                    $uartX().c4.modify(|_, w| w.maen1().clear_bit().maen2().clear_bit());
+                }
+
+                /// Discards every frame but the addresses given, e.g. at the end of a message
+                pub fn enable_address_match(&mut self, address1: Option<u8>, address2: Option<u8>) {
+                    let uart = $uartX();
+
+                    // Reference: 47.3.9 UART Match Address Registers 1 (UART_MA1)
+                    uart.ma1.write(|w| unsafe { w.ma().bits(address1.unwrap_or(0)) });
+
+                    // Reference: 47.3.10 UART Match Address Registers 2 (UART_MA2)
+                    uart.ma2.write(|w| unsafe { w.ma().bits(address2.unwrap_or(0)) });
+
+                    // Reference: 47.3.11 UART Control Register 4 (UART_C4)
+                    uart.c4.modify(|_, w| {
+                        w.maen1().bit(address1.is_some());
+                        w.maen2().bit(address2.is_some())
+                    });
+                }
+
+                /// Puts the receiver in standby until the wake-up condition of its configuration,
+                /// ignoring the frames meant for other nodes
+                pub fn enter_standby(&mut self) {
+                    // Reference: 47.3.4 UART Control Register 2 (UART_C2)
+                    unsafe { bitband::set($uartX().c2(), 1) };
+                }
+
+                /// Returns `true` while the receiver is in standby
+                pub fn is_in_standby(&self) -> bool {
+                    $uartX().c2.read().rwu().bit_is_set()
+                }
+
+                /// Returns `true` once the line has been idle for a character time, e.g. at the end
+                /// of a packet; cleared by `clear_flags`
+                pub fn is_idle(&self) -> bool {
+                    $uartX().s1.read().idle().bit_is_set()
+                }
+
+                /// Returns `true` once a LIN break has been detected, see `Config::lin_break`
+                pub fn is_break_detected(&self) -> bool {
+                    // Reference: 47.3.6 UART Status Register 2 (UART_S2)
+                    $uartX().s2.read().lbkdif().bit_is_set()
+                }
+
+                /// Clears the LIN break detect flag
+                pub fn clear_break(&mut self) {
+                    // Reference: 47.3.6 UART Status Register 2 (UART_S2)
+                    // The flags of S2 are cleared by writing 1, RXEDGIF is left alone
+                    $uartX().s2.modify(|_, w| {
+                        w.rxedgif().clear_bit();
+                        w.lbkdif().set_bit()
+                    });
+                }
+
+                /// Clears the error and idle flags, dropping the data received with them
+                pub fn clear_flags(&mut self) {
                     let uart = $uartX();
                     let s1 = uart.s1.read();
 
-                    if s1.pf().bit_is_set() {
-                        return Err(nb::Error::Other(Error::Parity));
+                    // Reference: 47.3.5 UART Status Register 1 (UART_S1)
+                    // The flags are cleared by reading S1 then D
+                    if s1.or().bit_is_set() || s1.nf().bit_is_set() || s1.fe().bit_is_set() ||
+                        s1.pf().bit_is_set() || s1.idle().bit_is_set()
+                    {
+                        uart.d.read();
                     }
+                }
+            }
+
+            impl Tx<$UARTX> {
+                /// Starts listening for a transmitter interrupt event
+                pub fn listen(&mut self, event: TxEvent) {
+                    set_tx_event($uartX(), event, true);
+                }
+
+                /// Stops listening for a transmitter interrupt event
+                pub fn unlisten(&mut self, event: TxEvent) {
+                    set_tx_event($uartX(), event, false);
+                }
 
-                    if s1.fe().bit_is_set() {
-                        return Err(nb::Error::Other(Error::Framing));
+                /// Queues a break character, 13 bits long with `Config::lin_break`, otherwise as
+                /// long as a frame
+                pub fn send_break(&mut self) -> nb::Result<(), Error> {
+                    if $uartX().s1.read().tdre().bit_is_clear() {
+                        return Err(nb::Error::WouldBlock);
                     }
 
-                    if s1.nf().bit_is_set() {
-                        return Err(nb::Error::Other(Error::Noise));
+                    // Reference: 47.4.1.1 Send break and queued idle
+                    // Toggling SBK queues a single break character
+                    unsafe {
+                        bitband::set($uartX().c2(), 0);
+                        bitband::clear($uartX().c2(), 0);
                     }
 
-                    if s1.or().bit_is_set() {
-                        return Err(nb::Error::Other(Error::Overrun));
+                    Ok(())
+                }
+            }
+
+            impl serial::Read<u8> for Rx<$UARTX> {
+                type Error = Error;
+
+                fn read(&mut self) -> nb::Result<u8, Error> {
+                    let uart = $uartX();
+                    let s1 = uart.s1.read();
+
+                    let error = if s1.pf().bit_is_set() {
+                        Some(Error::Parity)
+                    } else if s1.fe().bit_is_set() {
+                        Some(Error::Framing)
+                    } else if s1.nf().bit_is_set() {
+                        Some(Error::Noise)
+                    } else if s1.or().bit_is_set() {
+                        Some(Error::Overrun)
+                    } else {
+                        None
+                    };
+
+                    if let Some(error) = error {
+                        // Reference: 47.3.5 UART Status Register 1 (UART_S1)
+                        // The flags are cleared by reading S1 then D, which drops the faulty data
+                        uart.d.read();
+                        return Err(nb::Error::Other(error));
                     }
 
-                    if s1.rdrf().bit_is_clear() {
+                    // With the FIFO enabled, RDRF is only set above the watermark
+                    if s1.rdrf().bit_is_clear() && uart.rcfifo.read().rxcount().bits() == 0 {
                         return Err(nb::Error::WouldBlock);
                     }
 
@@ -202,14 +963,25 @@ macro_rules! hal {
                 }
             }
 
+            impl serial::Read<u16> for Rx<$UARTX> {
+                type Error = Error;
+
+                /// Reads a 9-bit word, the ninth bit being C3.R8
+                fn read(&mut self) -> nb::Result<u16, Error> {
+                    // R8 must be read before D, as reading D completes the reception
+                    let r8 = $uartX().c3.read().r8().bit();
+                    let byte: u8 = serial::Read::<u8>::read(self)?;
+
+                    Ok(u16::from(byte) | u16::from(r8) << 8)
+                }
+            }
+
             impl serial::Write<u8> for Tx<$UARTX> {
-                // The only possible errors during
-                // transmission are: clear to send (which is disabled in this case) errors and
-                // framing errors (which only occur in SmartCard mode); neither of these apply to
-                // our hardware configuration
-                type Error = Void;
+                // The only possible transmission errors are framing errors, which only occur in
+                // ISO-7816 mode; a deasserted CTS pin makes the writes return `WouldBlock`
+                type Error = Error;
 
-                fn flush(&mut self) -> nb::Result<(), Void> {
+                fn flush(&mut self) -> nb::Result<(), Error> {
                     if $uartX().s1.read().tc().bit_is_clear() {
                         return Err(nb::Error::WouldBlock);
                     }
@@ -217,7 +989,7 @@ macro_rules! hal {
                     Ok(())
                 }
 
-                fn write(&mut self, byte: u8) -> nb::Result<(), Void> {
+                fn write(&mut self, byte: u8) -> nb::Result<(), Error> {
                     let uart = $uartX();
 
                     if uart.s1.read().tdre().bit_is_clear() {
@@ -229,24 +1001,452 @@ macro_rules! hal {
                     Ok(())
                 }
             }
+
+            impl serial::Write<u16> for Tx<$UARTX> {
+                type Error = Error;
+
+                fn flush(&mut self) -> nb::Result<(), Error> {
+                    serial::Write::<u8>::flush(self)
+                }
+
+                /// Writes a 9-bit word, the ninth bit going to C3.T8
+                ///
+                /// With 2 stop bits, T8 is the second stop bit: it stays set and the ninth bit of
+                /// the word is ignored.
+                fn write(&mut self, word: u16) -> nb::Result<(), Error> {
+                    let uart = $uartX();
+
+                    if uart.s1.read().tdre().bit_is_clear() {
+                        return Err(nb::Error::WouldBlock);
+                    }
+
+                    // Reference: 47.3.7 UART Control Register 3 (UART_C3)
+                    let t8 = self.two_stop_bits || word.get_bit(8);
+                    unsafe { bitband::write(uart.c3(), 6, t8) };
+                    uart.d.write(|w| unsafe { w.bits(word as u8) });
+
+                    Ok(())
+                }
+            }
         )+
     }
 }
 
 hal! {
+    UART0: (uart0, uart0_rs485, uart0_flow_control, core),
+    UART1: (uart1, uart1_rs485, uart1_flow_control, core),
+    UART2: (uart2, uart2_rs485, uart2_flow_control, bus),
+}
+
+// UART3 and UART4 are only bonded out on the larger packages
+#[cfg(feature = "100lqfp")]
+hal! {
+    UART3: (uart3, uart3_rs485, uart3_flow_control, bus),
+    UART4: (uart4, uart4_rs485, uart4_flow_control, bus),
+}
+
+// Reference: 47.3.16 UART FIFO Parameters (UART_PFIFO)
+macro_rules! fifo {
+    ($(
+        $UARTX:ident: ($uartX:ident),
+    )+) => {
+        $(
+            impl<PINS> Serial<$UARTX, PINS> {
+                /// Enables the TX and RX FIFOs
+                ///
+                /// TDRE is set while at most `tx_watermark` words are in the TX FIFO, RDRF while
+                /// more than `rx_watermark` words are in the RX FIFO.
+                pub fn enable_fifo(&mut self, tx_watermark: u8, rx_watermark: u8) {
+                    let uart = &*self.uart;
+                    let pfifo = uart.pfifo.read();
+
+                    if tx_watermark >= fifo_depth(pfifo.txfifosize().bits()) {
+                        panic!("Invalid TX FIFO watermark: {}", tx_watermark);
+                    }
+
+                    if rx_watermark == 0 || rx_watermark > fifo_depth(pfifo.rxfifosize().bits()) {
+                        panic!("Invalid RX FIFO watermark: {}", rx_watermark);
+                    }
+
+                    // The FIFOs can only be changed while the transmitter and receiver are disabled
+                    let c2 = uart.c2.read().bits();
+                    uart.c2.modify(|_, w| w.te().clear_bit().re().clear_bit());
+
+                    uart.pfifo.modify(|_, w| w.txfe().set_bit().rxfe().set_bit());
+                    uart.cfifo.modify(|_, w| w.txflush().set_bit().rxflush().set_bit());
+
+                    // Reference: 47.3.19 UART FIFO Transmit Watermark (UART_TWFIFO)
+                    uart.twfifo.write(|w| unsafe { w.txwater().bits(tx_watermark) });
+
+                    // Reference: 47.3.21 UART FIFO Receive Watermark (UART_RWFIFO)
+                    uart.rwfifo.write(|w| unsafe { w.rxwater().bits(rx_watermark) });
+
+                    uart.c2.write(|w| unsafe { w.bits(c2) });
+                }
+
+                /// Disables the TX and RX FIFOs, discarding their content
+                pub fn disable_fifo(&mut self) {
+                    let uart = &*self.uart;
+
+                    let c2 = uart.c2.read().bits();
+                    uart.c2.modify(|_, w| w.te().clear_bit().re().clear_bit());
+
+                    uart.cfifo.modify(|_, w| w.txflush().set_bit().rxflush().set_bit());
+                    uart.pfifo.modify(|_, w| w.txfe().clear_bit().rxfe().clear_bit());
+                    uart.twfifo.write(|w| unsafe { w.txwater().bits(0) });
+                    uart.rwfifo.write(|w| unsafe { w.rxwater().bits(1) });
+
+                    uart.c2.write(|w| unsafe { w.bits(c2) });
+                }
+            }
+
+            impl Rx<$UARTX> {
+                /// Returns the number of words in the RX FIFO
+                pub fn fifo_count(&self) -> u8 {
+                    // Reference: 47.3.22 UART FIFO Receive Count (UART_RCFIFO)
+                    $uartX().rcfifo.read().rxcount().bits()
+                }
+
+                /// Discards the content of the RX FIFO
+                pub fn flush_fifo(&mut self) {
+                    // Reference: 47.3.17 UART FIFO Control Register (UART_CFIFO)
+                    $uartX().cfifo.modify(|_, w| w.rxflush().set_bit());
+                }
+
+                /// Reads the words available in the RX FIFO into `buffer`, returning how many
+                /// were read
+                ///
+                /// While the FIFO is disabled, at most the word of the data register is read.
+                pub fn read_fifo(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+                    let uart = $uartX();
+
+                    // Reference: 47.3.18 UART FIFO Status Register (UART_SFIFO)
+                    if uart.sfifo.read().rxuf().bit_is_set() {
+                        uart.sfifo.write(|w| w.rxuf().set_bit());
+                        return Err(Error::FifoUnderflow);
+                    }
+
+                    // Without the FIFO, only the data register holds a word
+                    let available = if uart.pfifo.read().rxfe().bit_is_set() {
+                        self.fifo_count()
+                    } else {
+                        u8::from(uart.s1.read().rdrf().bit_is_set())
+                    };
+
+                    let count = usize::from(available).min(buffer.len());
+                    for byte in &mut buffer[..count] {
+                        *byte = match serial::Read::<u8>::read(self) {
+                            Ok(byte) => byte,
+                            Err(nb::Error::Other(error)) => return Err(error),
+                            Err(nb::Error::WouldBlock) => return Err(Error::FifoUnderflow),
+                        };
+                    }
+
+                    Ok(count)
+                }
+            }
+
+            impl Tx<$UARTX> {
+                /// Returns the number of words in the TX FIFO
+                pub fn fifo_count(&self) -> u8 {
+                    // Reference: 47.3.20 UART FIFO Transmit Count (UART_TCFIFO)
+                    $uartX().tcfifo.read().txcount().bits()
+                }
+
+                /// Discards the words of the TX FIFO that have not been sent yet
+                pub fn flush_fifo(&mut self) {
+                    // Reference: 47.3.17 UART FIFO Control Register (UART_CFIFO)
+                    $uartX().cfifo.modify(|_, w| w.txflush().set_bit());
+                }
+
+                /// Fills the TX FIFO with the start of `bytes`, returning how many were queued
+                ///
+                /// While the FIFO is disabled, at most one byte is queued in the data register.
+                pub fn write_fifo(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+                    let uart = $uartX();
+
+                    // Reference: 47.3.18 UART FIFO Status Register (UART_SFIFO)
+                    if uart.sfifo.read().txof().bit_is_set() {
+                        uart.sfifo.write(|w| w.txof().set_bit());
+                        return Err(Error::FifoOverflow);
+                    }
+
+                    // TXFIFOSIZE is reported even when the FIFO is disabled, the data register
+                    // then holding a single word
+                    let pfifo = uart.pfifo.read();
+                    let free = if pfifo.txfe().bit_is_set() {
+                        fifo_depth(pfifo.txfifosize().bits()) - self.fifo_count()
+                    } else {
+                        u8::from(uart.s1.read().tdre().bit_is_set())
+                    };
+
+                    let count = usize::from(free).min(bytes.len());
+                    for &byte in &bytes[..count] {
+                        uart.d.write(|w| unsafe { w.bits(byte) });
+                    }
+
+                    Ok(count)
+                }
+            }
+        )+
+    }
+}
+
+fifo! {
     UART0: (uart0),
     UART1: (uart1),
-    UART2: (uart2),
 }
 
-// Euclid's GCD
-fn gcd(numerator: u32, denominator: u32) -> u32 {
-    let mut numerator = numerator;
-    let mut denominator = denominator;
-    while denominator != 0 {
-        let temp = denominator;
-        denominator = numerator % denominator;
-        numerator = temp;
+impl<TX, RX> Serial<UART0, (TX, RX)> {
+    /// Configures UART0 for an IrDA transceiver, each 0 bit being sent and received as a pulse of
+    /// `pulse_width`
+    pub fn uart0_irda(
+        uart: UART0,
+        pins: (TX, RX),
+        config: Config,
+        clocks: &Clocks,
+        pulse_width: IrdaPulseWidth,
+    ) -> Result<Self, Error>
+    where
+        TX: TxPin<UART0>,
+        RX: RxPin<UART0>,
+    {
+        let baud_rate = Self::configure(&uart, &config, clocks, Mode::Irda(pulse_width))?;
+
+        let two_stop_bits = config.stop_bits == StopBits::STOP2;
+
+        Ok(Serial { uart, pins, baud_rate, cts: None, two_stop_bits })
+    }
+}
+
+impl<PINS> Serial<UART0, PINS> {
+    /// Modulates the TX pin with the output of an FTM channel, e.g. the carrier of a consumer
+    /// infrared LED
+    ///
+    /// The FTM channel has to be configured separately.
+    pub fn set_tx_modulation(&mut self, sopt5: &SOPT5, modulation: TxModulation) {
+        // Reference: 12.2.5 System Options Register 5 (SIM_SOPT5)
+        sopt5.modify(|_, w| match modulation {
+            TxModulation::None => w.uart0txsrc()._00(),
+            TxModulation::Ftm1Channel0 => w.uart0txsrc()._01(),
+            TxModulation::Ftm2Channel0 => w.uart0txsrc()._10(),
+        });
     }
-    numerator
+}
+
+macro_rules! dma {
+    ($(
+        $UARTX:ident: ($uartX:ident, $rx_source:expr, $tx_source:expr),
+    )+) => {
+        $(
+            impl Tx<$UARTX> {
+                /// Sends `buffer` through a DMA channel
+                pub fn write_all_dma<CHANNEL>(
+                    self,
+                    buffer: &'static [u8],
+                    mut channel: CHANNEL,
+                ) -> Transfer<CHANNEL, &'static [u8], Self>
+                where
+                    CHANNEL: Channel,
+                {
+                    let uart = $uartX();
+
+                    channel.set_request_source($tx_source);
+                    // The buffer is owned by the transfer until it completes
+                    unsafe {
+                        channel.set_source(buffer.as_ptr() as u32, 1, 0);
+                        channel.set_destination(&uart.d as *const _ as u32, 0, 0);
+                    }
+                    channel.set_count(dma_count(buffer.len()));
+                    channel.set_disable_request(true);
+
+                    // Reference: 47.3.12 UART Control Register 5 (UART_C5)
+                    unsafe { bitband::set(uart.c5(), 7) };
+                    set_tx_event(uart, TxEvent::Txe, true);
+
+                    Transfer::start(channel, buffer, self)
+                }
+            }
+
+            impl Rx<$UARTX> {
+                /// Fills `buffer` through a DMA channel
+                pub fn read_exact_dma<CHANNEL>(
+                    self,
+                    buffer: &'static mut [u8],
+                    mut channel: CHANNEL,
+                ) -> Transfer<CHANNEL, &'static mut [u8], Self>
+                where
+                    CHANNEL: Channel,
+                {
+                    self.configure_dma(&mut channel, buffer, true);
+                    Transfer::start(channel, buffer, self)
+                }
+
+                /// Receives into `buffer` through a DMA channel, wrapping around at its end until
+                /// the transfer is stopped
+                pub fn circular_read_dma<CHANNEL>(
+                    self,
+                    buffer: &'static mut [u8],
+                    mut channel: CHANNEL,
+                ) -> CircularTransfer<CHANNEL, Self>
+                where
+                    CHANNEL: Channel,
+                {
+                    self.configure_dma(&mut channel, buffer, false);
+                    CircularTransfer::start(channel, buffer, self)
+                }
+
+                fn configure_dma<CHANNEL>(&self, channel: &mut CHANNEL, buffer: &mut [u8], once: bool)
+                where
+                    CHANNEL: Channel,
+                {
+                    let uart = $uartX();
+                    let count = dma_count(buffer.len());
+
+                    channel.set_request_source($rx_source);
+                    // The buffer is owned by the transfer until it is stopped
+                    unsafe {
+                        channel.set_source(&uart.d as *const _ as u32, 0, 0);
+                        channel.set_destination(
+                            buffer.as_mut_ptr() as u32,
+                            1,
+                            if once { 0 } else { -i32::from(count) },
+                        );
+                    }
+                    channel.set_count(count);
+                    channel.set_disable_request(once);
+
+                    // Reference: 47.3.12 UART Control Register 5 (UART_C5)
+                    unsafe { bitband::set(uart.c5(), 5) };
+                    set_rx_event(uart, RxEvent::Rxne, true);
+                }
+            }
+
+            impl Payload for Tx<$UARTX> {
+                fn stop_dma(&mut self) {
+                    let uart = $uartX();
+                    set_tx_event(uart, TxEvent::Txe, false);
+                    unsafe { bitband::clear(uart.c5(), 7) };
+                }
+            }
+
+            impl Payload for Rx<$UARTX> {
+                fn stop_dma(&mut self) {
+                    let uart = $uartX();
+                    set_rx_event(uart, RxEvent::Rxne, false);
+                    unsafe { bitband::clear(uart.c5(), 5) };
+                }
+            }
+        )+
+    }
+}
+
+// Reference: 20.1.1 DMA request sources
+dma! {
+    UART0: (uart0, 2, 3),
+    UART1: (uart1, 4, 5),
+    UART2: (uart2, 6, 7),
+}
+
+#[cfg(feature = "100lqfp")]
+dma! {
+    UART3: (uart3, 8, 9),
+    UART4: (uart4, 10, 11),
+}
+
+fn dma_count(length: usize) -> u16 {
+    if length == 0 || length > dma::COUNT_MAX {
+        panic!("Invalid DMA buffer length: {}", length);
+    }
+
+    length as u16
+}
+
+// Reference: 47.3.16 UART FIFO Parameters (UART_PFIFO)
+fn fifo_depth(size: u8) -> u8 {
+    match size {
+        0 => 1,
+        1..=6 => 2 << size,
+        _ => panic!("Invalid UART FIFO size: {}", size),
+    }
+}
+
+// The interrupt and DMA enables are set through their bit-band alias, as the receiver and
+// transmitter halves share BDH, C2, C3 and C5. The registers are 8 bits wide, so their aliases are
+// accessed a byte at a time and the neighbouring S1, S2 and D are never read or written back.
+trait ControlRegisters {
+    fn bdh(&self) -> *const u8;
+    fn c2(&self) -> *const u8;
+    fn c3(&self) -> *const u8;
+    fn c5(&self) -> *const u8;
+}
+
+// Reference: 47.3.1 UART Baud Rate Registers: High (UART_BDH)
+// Reference: 47.3.4 UART Control Register 2 (UART_C2)
+// Reference: 47.3.7 UART Control Register 3 (UART_C3)
+fn set_rx_event<UART: ControlRegisters>(uart: &UART, event: RxEvent, enabled: bool) {
+    unsafe {
+        match event {
+            RxEvent::Rxne => bitband::write(uart.c2(), 5, enabled),
+            RxEvent::Idle => bitband::write(uart.c2(), 4, enabled),
+            RxEvent::Overrun => bitband::write(uart.c3(), 3, enabled),
+            RxEvent::Noise => bitband::write(uart.c3(), 2, enabled),
+            RxEvent::Framing => bitband::write(uart.c3(), 1, enabled),
+            RxEvent::Parity => bitband::write(uart.c3(), 0, enabled),
+            RxEvent::LinBreak => bitband::write(uart.bdh(), 7, enabled),
+        }
+    }
+}
+
+// Routes an event to the half of the UART it belongs to
+fn set_event<UART: ControlRegisters>(uart: &UART, event: Event, enabled: bool) {
+    match event {
+        Event::Rxne => set_rx_event(uart, RxEvent::Rxne, enabled),
+        Event::Txe => set_tx_event(uart, TxEvent::Txe, enabled),
+        Event::Tc => set_tx_event(uart, TxEvent::Tc, enabled),
+        Event::Idle => set_rx_event(uart, RxEvent::Idle, enabled),
+        Event::Overrun => set_rx_event(uart, RxEvent::Overrun, enabled),
+        Event::Noise => set_rx_event(uart, RxEvent::Noise, enabled),
+        Event::Framing => set_rx_event(uart, RxEvent::Framing, enabled),
+        Event::Parity => set_rx_event(uart, RxEvent::Parity, enabled),
+        Event::LinBreak => set_rx_event(uart, RxEvent::LinBreak, enabled),
+    }
+}
+
+// Reference: 47.3.4 UART Control Register 2 (UART_C2)
+fn set_tx_event<UART: ControlRegisters>(uart: &UART, event: TxEvent, enabled: bool) {
+    unsafe {
+        match event {
+            TxEvent::Txe => bitband::write(uart.c2(), 7, enabled),
+            TxEvent::Tc => bitband::write(uart.c2(), 6, enabled),
+        }
+    }
+}
+
+// Reference: 47.4.4 Baud rate generation
+// The baud rate is clock / (16 * (SBR + BRFA / 32)), so the divisor is counted in 1/32 steps
+fn baud_rate_divisor(clock: Hertz<u32>, baud_rate: Bps<u32>) -> Result<(u16, u8, Bps<u32>), Error> {
+    if baud_rate.0 == 0 {
+        return Err(Error::BaudRate);
+    }
+
+    let divisor_low = clock.0 * 2 / baud_rate.0;
+    let baud_rate_of = |divisor: u32| (clock.0 * 2).checked_div(divisor).unwrap_or(u32::MAX);
+    let error_of = |divisor: u32| baud_rate_of(divisor).abs_diff(baud_rate.0);
+
+    let divisor = if error_of(divisor_low + 1) < error_of(divisor_low) {
+        divisor_low + 1
+    } else {
+        divisor_low
+    };
+
+    if !(32..=MODULE_CLOCK_DIVISOR_MAX * 32 + 31).contains(&divisor) {
+        return Err(Error::BaudRate);
+    }
+
+    if u64::from(error_of(divisor)) * 1000 > u64::from(baud_rate.0) * u64::from(BAUD_RATE_TOLERANCE) {
+        return Err(Error::BaudRate);
+    }
+
+    Ok(((divisor / 32) as u16, (divisor % 32) as u8, Bps(baud_rate_of(divisor))))
 }
@@ -0,0 +1,518 @@
+//! Interrupt driven serial communication through ring buffers
+//!
+//! `BufferedSerial::new` splits the receiver and transmitter halves of a UART and two user
+//! provided buffers into an application side, `BufferedSerial`, and an interrupt side,
+//! `InterruptHandler`. The UART interrupt handler calls `InterruptHandler::handle_interrupt`, which
+//! moves the received data into the RX buffer and the queued data out of the TX buffer; the
+//! application reads and writes the buffers without blocking. Each buffer has a single producer
+//! and a single consumer, one of them on each side, so no critical section is needed.
+//!
+//! The buffers are borrowed for `'static`, e.g. from `cortex_m::singleton!`, as the interrupt
+//! handler keeps using them.
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::slice;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use hal::serial;
+use mk20d7::{UART0, UART1, UART2};
+#[cfg(feature = "100lqfp")]
+use mk20d7::{UART3, UART4};
+use nb;
+use void::Void;
+
+use super::{set_tx_event, Error, Rx, RxEvent, Tx, TxEvent};
+
+/// Single producer, single consumer ring buffer
+///
+/// One slot of the buffer is kept free to tell a full queue from an empty one. The queue is used
+/// through the `Producer` and `Consumer` handles of `split`.
+pub struct Queue<'a> {
+    buffer: *mut u8,
+    capacity: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    _buffer: PhantomData<&'a mut [u8]>,
+}
+
+/// Producer handle of a `Queue`
+pub struct Producer<'q, 'a: 'q> {
+    queue: &'q Queue<'a>,
+}
+
+/// Consumer handle of a `Queue`
+pub struct Consumer<'q, 'a: 'q> {
+    queue: &'q Queue<'a>,
+}
+
+impl<'a> Queue<'a> {
+    /// Creates an empty queue holding up to `buffer.len() - 1` bytes
+    pub fn new(buffer: &'a mut [u8]) -> Queue<'a> {
+        if buffer.len() < 2 {
+            panic!("Invalid queue buffer length: {}", buffer.len());
+        }
+
+        Queue {
+            buffer: buffer.as_mut_ptr(),
+            capacity: buffer.len(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            _buffer: PhantomData,
+        }
+    }
+
+    /// Splits the queue into its producer and consumer handles
+    pub fn split<'q>(&'q mut self) -> (Producer<'q, 'a>, Consumer<'q, 'a>) {
+        (Producer { queue: self }, Consumer { queue: self })
+    }
+
+    /// Returns the number of queued bytes
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        (tail + self.capacity - head) % self.capacity
+    }
+
+    /// Returns `true` if no byte is queued
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of bytes the queue can hold
+    pub fn capacity(&self) -> usize {
+        self.capacity - 1
+    }
+
+    /// Releases the buffer
+    pub fn free(self) -> &'a mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.buffer, self.capacity) }
+    }
+
+    /// Appends `byte`, giving it back if the queue is full
+    ///
+    /// # Safety
+    ///
+    /// No other context may call `enqueue` at the same time.
+    pub unsafe fn enqueue(&self, byte: u8) -> Result<(), u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let next = (tail + 1) % self.capacity;
+
+        if next == self.head.load(Ordering::Acquire) {
+            return Err(byte);
+        }
+
+        self.buffer.add(tail).write_volatile(byte);
+        self.tail.store(next, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Removes the oldest byte
+    ///
+    /// # Safety
+    ///
+    /// No other context may call `dequeue` at the same time.
+    pub unsafe fn dequeue(&self) -> Option<u8> {
+        let head = self.head.load(Ordering::Relaxed);
+
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let byte = self.buffer.add(head).read_volatile();
+        self.head.store((head + 1) % self.capacity, Ordering::Release);
+
+        Some(byte)
+    }
+}
+
+// The producer and the consumer only ever write their own index, and `split` hands out a single
+// handle of each
+unsafe impl<'a> Send for Queue<'a> {}
+unsafe impl<'a> Sync for Queue<'a> {}
+
+impl<'q, 'a> Producer<'q, 'a> {
+    /// Appends `byte`, giving it back if the queue is full
+    pub fn enqueue(&mut self, byte: u8) -> Result<(), u8> {
+        // The producer handle is unique and borrowed mutably
+        unsafe { self.queue.enqueue(byte) }
+    }
+
+    /// Returns the number of queued bytes
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no byte is queued
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+impl<'q, 'a> Consumer<'q, 'a> {
+    /// Removes the oldest byte
+    pub fn dequeue(&mut self) -> Option<u8> {
+        // The consumer handle is unique and borrowed mutably
+        unsafe { self.queue.dequeue() }
+    }
+
+    /// Returns the number of queued bytes
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no byte is queued
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// Error seen by the interrupt handler, reported by the next read
+///
+/// Only the interrupt handler sets it and only the application takes it.
+struct PendingError(UnsafeCell<Option<Error>>, AtomicUsize);
+
+const NO_ERROR: usize = 0;
+const PENDING_ERROR: usize = 1;
+
+impl PendingError {
+    fn new() -> PendingError {
+        PendingError(UnsafeCell::new(None), AtomicUsize::new(NO_ERROR))
+    }
+
+    // The first error is kept until the application takes it
+    fn set(&self, error: Error) {
+        if self.1.load(Ordering::Acquire) == PENDING_ERROR {
+            return;
+        }
+
+        unsafe { *self.0.get() = Some(error) };
+        self.1.store(PENDING_ERROR, Ordering::Release);
+    }
+
+    fn take(&self) -> Option<Error> {
+        if self.1.load(Ordering::Acquire) == NO_ERROR {
+            return None;
+        }
+
+        let error = unsafe { (*self.0.get()).take() };
+        self.1.store(NO_ERROR, Ordering::Release);
+        error
+    }
+}
+
+/// Buffers shared by the application and the interrupt handler
+pub struct Buffers {
+    rx_queue: Queue<'static>,
+    tx_queue: Queue<'static>,
+    error: PendingError,
+}
+
+// The queues and the pending error are each shared by one producer and one consumer
+unsafe impl Sync for Buffers {}
+
+impl Buffers {
+    /// Wraps the RX and TX buffers
+    pub fn new(rx_buffer: &'static mut [u8], tx_buffer: &'static mut [u8]) -> Buffers {
+        Buffers {
+            rx_queue: Queue::new(rx_buffer),
+            tx_queue: Queue::new(tx_buffer),
+            error: PendingError::new(),
+        }
+    }
+
+    /// Releases the RX and TX buffers
+    pub fn free(self) -> (&'static mut [u8], &'static mut [u8]) {
+        (self.rx_queue.free(), self.tx_queue.free())
+    }
+}
+
+/// Application side of a serial interface buffered in RAM
+pub struct BufferedSerial<UART> {
+    buffers: *mut Buffers,
+    rx_queue: Consumer<'static, 'static>,
+    tx_queue: Producer<'static, 'static>,
+    error: &'static PendingError,
+    _uart: PhantomData<UART>,
+}
+
+/// Interrupt side of a serial interface buffered in RAM, fed by the UART interrupt
+pub struct InterruptHandler<UART> {
+    rx: Rx<UART>,
+    tx: Tx<UART>,
+    rx_queue: Producer<'static, 'static>,
+    tx_queue: Consumer<'static, 'static>,
+    error: &'static PendingError,
+}
+
+// Each side owns one end of each queue, the buffers are only reached again by `free`
+unsafe impl<UART> Send for BufferedSerial<UART> {}
+unsafe impl<UART> Send for InterruptHandler<UART> {}
+
+macro_rules! buffered {
+    ($(
+        $UARTX:ident: ($uartX:ident),
+    )+) => {
+        $(
+            impl BufferedSerial<$UARTX> {
+                /// Buffers the serial halves and enables the receive interrupt
+                ///
+                /// The UART interrupt must be unmasked in the NVIC and its handler must call
+                /// `InterruptHandler::handle_interrupt`.
+                pub fn new(
+                    mut rx: Rx<$UARTX>,
+                    tx: Tx<$UARTX>,
+                    buffers: &'static mut Buffers,
+                ) -> (Self, InterruptHandler<$UARTX>) {
+                    let pointer: *mut Buffers = buffers;
+                    let buffers: &'static mut Buffers = unsafe { &mut *pointer };
+
+                    let (rx_producer, rx_consumer) = buffers.rx_queue.split();
+                    let (tx_producer, tx_consumer) = buffers.tx_queue.split();
+                    let error = &buffers.error;
+
+                    rx.clear_flags();
+                    rx.listen(RxEvent::Rxne);
+                    rx.listen(RxEvent::Overrun);
+
+                    let serial = BufferedSerial {
+                        buffers: pointer,
+                        rx_queue: rx_consumer,
+                        tx_queue: tx_producer,
+                        error,
+                        _uart: PhantomData,
+                    };
+
+                    let handler = InterruptHandler {
+                        rx,
+                        tx,
+                        rx_queue: rx_producer,
+                        tx_queue: tx_consumer,
+                        error,
+                    };
+
+                    (serial, handler)
+                }
+
+                /// Reads a received byte
+                pub fn read(&mut self) -> nb::Result<u8, Error> {
+                    if let Some(error) = self.error.take() {
+                        return Err(nb::Error::Other(error));
+                    }
+
+                    self.rx_queue.dequeue().ok_or(nb::Error::WouldBlock)
+                }
+
+                /// Queues a byte for transmission
+                pub fn write(&mut self, byte: u8) -> nb::Result<(), Void> {
+                    self.tx_queue.enqueue(byte).map_err(|_| nb::Error::WouldBlock)?;
+
+                    // The interrupt handler disables TDRE interrupts once the queue is empty
+                    set_tx_event(super::$uartX(), TxEvent::Txe, true);
+
+                    Ok(())
+                }
+
+                /// Returns the number of received bytes waiting to be read
+                pub fn available(&self) -> usize {
+                    self.rx_queue.len()
+                }
+
+                /// Stops the interrupts and releases the serial halves and the buffers
+                pub fn free(
+                    self,
+                    handler: InterruptHandler<$UARTX>,
+                ) -> (Rx<$UARTX>, Tx<$UARTX>, &'static mut Buffers) {
+                    let InterruptHandler { mut rx, mut tx, .. } = handler;
+
+                    rx.unlisten(RxEvent::Rxne);
+                    rx.unlisten(RxEvent::Overrun);
+                    tx.unlisten(TxEvent::Txe);
+
+                    // Both sides are consumed, so no handle to the buffers is left
+                    (rx, tx, unsafe { &mut *self.buffers })
+                }
+            }
+
+            impl InterruptHandler<$UARTX> {
+                /// Moves data between the UART and the buffers, to be called from the UART
+                /// interrupt handler
+                pub fn handle_interrupt(&mut self) {
+                    let uart = super::$uartX();
+
+                    loop {
+                        match serial::Read::<u8>::read(&mut self.rx) {
+                            Ok(byte) => if self.rx_queue.enqueue(byte).is_err() {
+                                self.error.set(Error::Overrun);
+                            },
+                            Err(nb::Error::Other(error)) => self.error.set(error),
+                            Err(nb::Error::WouldBlock) => break,
+                        }
+                    }
+
+                    while uart.s1.read().tdre().bit_is_set() {
+                        match self.tx_queue.dequeue() {
+                            Some(byte) => uart.d.write(|w| unsafe { w.bits(byte) }),
+                            None => {
+                                self.tx.unlisten(TxEvent::Txe);
+                                break;
+                            },
+                        }
+                    }
+                }
+            }
+
+            impl serial::Read<u8> for BufferedSerial<$UARTX> {
+                type Error = Error;
+
+                fn read(&mut self) -> nb::Result<u8, Error> {
+                    BufferedSerial::<$UARTX>::read(self)
+                }
+            }
+
+            impl serial::Write<u8> for BufferedSerial<$UARTX> {
+                type Error = Void;
+
+                fn flush(&mut self) -> nb::Result<(), Void> {
+                    if !self.tx_queue.is_empty() || super::$uartX().s1.read().tc().bit_is_clear() {
+                        return Err(nb::Error::WouldBlock);
+                    }
+
+                    Ok(())
+                }
+
+                fn write(&mut self, byte: u8) -> nb::Result<(), Void> {
+                    BufferedSerial::<$UARTX>::write(self, byte)
+                }
+            }
+        )+
+    }
+}
+
+buffered! {
+    UART0: (uart0),
+    UART1: (uart1),
+    UART2: (uart2),
+}
+
+#[cfg(feature = "100lqfp")]
+buffered! {
+    UART3: (uart3),
+    UART4: (uart4),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Queue;
+
+    #[test]
+    fn queue_is_fifo() {
+        let mut buffer = [0; 4];
+        let mut queue = Queue::new(&mut buffer);
+        let (mut producer, mut consumer) = queue.split();
+
+        assert_eq!(consumer.dequeue(), None);
+        producer.enqueue(1).unwrap();
+        producer.enqueue(2).unwrap();
+        assert_eq!(consumer.len(), 2);
+        assert_eq!(consumer.dequeue(), Some(1));
+        assert_eq!(consumer.dequeue(), Some(2));
+        assert_eq!(consumer.dequeue(), None);
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn queue_keeps_one_slot_free() {
+        let mut buffer = [0; 4];
+        let mut queue = Queue::new(&mut buffer);
+        assert_eq!(queue.capacity(), 3);
+
+        let (mut producer, consumer) = queue.split();
+        for byte in 0..3 {
+            producer.enqueue(byte).unwrap();
+        }
+        assert_eq!(producer.enqueue(3), Err(3));
+        assert_eq!(consumer.len(), 3);
+    }
+
+    #[test]
+    fn queue_wraps_around() {
+        let mut buffer = [0; 3];
+        let mut queue = Queue::new(&mut buffer);
+        let (mut producer, mut consumer) = queue.split();
+
+        for byte in 0..10 {
+            producer.enqueue(byte).unwrap();
+            assert_eq!(consumer.len(), 1);
+            assert_eq!(consumer.dequeue(), Some(byte));
+        }
+    }
+
+    // The interrupt handler preempts the application between any two queue operations
+    #[test]
+    fn queue_survives_interrupts_while_consuming() {
+        let mut buffer = [0; 8];
+        let mut queue = Queue::new(&mut buffer);
+        let (mut producer, mut consumer) = queue.split();
+        let mut accepted = [0u8; 300];
+        let mut accepted_count = 0;
+        let mut received = [0u8; 300];
+        let mut received_count = 0;
+        let mut next = 0u8;
+
+        for pass in 0..200 {
+            // Interrupt: the UART delivers a burst, dropping what does not fit
+            for _ in 0..(pass % 4) {
+                if producer.enqueue(next).is_ok() {
+                    accepted[accepted_count] = next;
+                    accepted_count += 1;
+                }
+                next = next.wrapping_add(1);
+            }
+
+            // Application: takes one byte per pass
+            if let Some(byte) = consumer.dequeue() {
+                received[received_count] = byte;
+                received_count += 1;
+            }
+        }
+
+        while let Some(byte) = consumer.dequeue() {
+            received[received_count] = byte;
+            received_count += 1;
+        }
+
+        assert_eq!(&received[..received_count], &accepted[..accepted_count]);
+    }
+
+    #[test]
+    fn queue_survives_interrupts_while_producing() {
+        let mut buffer = [0; 5];
+        let mut queue = Queue::new(&mut buffer);
+        let (mut producer, mut consumer) = queue.split();
+        let mut sent = [0u8; 64];
+        let mut count = 0;
+        let mut next = 0u8;
+
+        while next < 64 || !consumer.is_empty() {
+            // Application: queues as much as fits
+            while next < 64 && producer.enqueue(next).is_ok() {
+                next += 1;
+            }
+
+            // Interrupt: the transmitter takes at most two bytes
+            for _ in 0..2 {
+                if let Some(byte) = consumer.dequeue() {
+                    sent[count] = byte;
+                    count += 1;
+                }
+            }
+        }
+
+        assert_eq!(count, 64);
+        for (index, &byte) in sent.iter().enumerate() {
+            assert_eq!(usize::from(byte), index);
+        }
+    }
+}
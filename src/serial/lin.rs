@@ -0,0 +1,496 @@
+//! LIN master and slave framing
+//!
+//! A LIN frame starts with a header sent by the master: a break, the sync byte and the protected
+//! identifier. The response follows, 1 to 8 data bytes and a checksum, sent by the master or by
+//! the slave the identifier belongs to. The UART must be configured with `Config::lin_break`.
+//!
+//! The transceiver echoes the bus to RX, so every byte sent is read back and compared to detect
+//! bit errors.
+
+use hal::serial::{Read, Write};
+use mk20d7::{UART0, UART1, UART2};
+#[cfg(feature = "100lqfp")]
+use mk20d7::{UART3, UART4};
+use nb;
+
+use super::{Rx, Tx};
+
+/// Sync byte, following the break of a header
+pub const SYNC: u8 = 0x55;
+
+/// Largest frame identifier
+pub const IDENTIFIER_MAX: u8 = 0x3F;
+
+/// Largest number of data bytes of a response
+pub const DATA_LENGTH_MAX: usize = 8;
+
+/// Identifier of the master request diagnostic frame
+pub const MASTER_REQUEST: u8 = 0x3C;
+
+/// Identifier of the slave response diagnostic frame
+pub const SLAVE_RESPONSE: u8 = 0x3D;
+
+/// Checksum of a response
+#[derive(Clone, Copy, PartialEq)]
+pub enum ChecksumModel {
+    /// Sum of the data bytes, LIN 1.x
+    Classic,
+
+    /// Sum of the protected identifier and the data bytes, LIN 2.x
+    ///
+    /// The diagnostic frames always use the classic checksum.
+    Enhanced,
+}
+
+/// LIN error
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// UART error
+    Serial(super::Error),
+
+    /// The byte following a break is not `SYNC`
+    Sync,
+
+    /// The parity bits of the protected identifier are wrong
+    Identifier,
+
+    /// The checksum of the response is wrong
+    Checksum,
+
+    /// A byte read back from the bus differs from the byte sent
+    Readback,
+}
+
+impl From<super::Error> for Error {
+    fn from(error: super::Error) -> Error {
+        Error::Serial(error)
+    }
+}
+
+/// Returns the protected identifier of a frame, its identifier and two parity bits
+pub fn protected_identifier(identifier: u8) -> u8 {
+    if identifier > IDENTIFIER_MAX {
+        panic!("Invalid LIN identifier: {}", identifier);
+    }
+
+    let bit = |n: u8| (identifier >> n) & 1;
+    let p0 = bit(0) ^ bit(1) ^ bit(2) ^ bit(4);
+    let p1 = !(bit(1) ^ bit(3) ^ bit(4) ^ bit(5)) & 1;
+
+    identifier | p0 << 6 | p1 << 7
+}
+
+/// Returns the identifier of a protected identifier, checking its parity bits
+pub fn identifier(protected_identifier: u8) -> Result<u8, Error> {
+    let identifier = protected_identifier & IDENTIFIER_MAX;
+
+    if self::protected_identifier(identifier) != protected_identifier {
+        return Err(Error::Identifier);
+    }
+
+    Ok(identifier)
+}
+
+/// Returns the checksum of a response: the inverted sum with carry of its bytes
+pub fn checksum(model: ChecksumModel, protected_identifier: u8, data: &[u8]) -> u8 {
+    let identifier = protected_identifier & IDENTIFIER_MAX;
+    let diagnostic = identifier == MASTER_REQUEST || identifier == SLAVE_RESPONSE;
+
+    let initial = if model == ChecksumModel::Enhanced && !diagnostic {
+        u16::from(protected_identifier)
+    } else {
+        0
+    };
+
+    let sum = data.iter().fold(initial, |sum, &byte| {
+        let sum = sum + u16::from(byte);
+        if sum > 0xFF { sum - 0xFF } else { sum }
+    });
+
+    !(sum as u8)
+}
+
+fn check_data_length(length: usize) {
+    if length == 0 || length > DATA_LENGTH_MAX {
+        panic!("Invalid LIN data length: {}", length);
+    }
+}
+
+/// Step of the reception of a header
+#[derive(Clone, Copy, PartialEq)]
+enum HeaderState {
+    Break,
+    Sync,
+    Identifier,
+}
+
+/// Decodes the headers seen on the bus
+struct HeaderReceiver {
+    state: HeaderState,
+}
+
+impl HeaderReceiver {
+    fn new() -> HeaderReceiver {
+        HeaderReceiver { state: HeaderState::Break }
+    }
+
+    fn on_break(&mut self) {
+        self.state = HeaderState::Sync;
+    }
+
+    // Returns the protected identifier once the header is complete
+    fn on_byte(&mut self, byte: u8) -> nb::Result<u8, Error> {
+        match self.state {
+            // Bytes outside of a header belong to a response to another node
+            HeaderState::Break => Err(nb::Error::WouldBlock),
+            HeaderState::Sync => if byte == SYNC {
+                self.state = HeaderState::Identifier;
+                Err(nb::Error::WouldBlock)
+            } else {
+                self.state = HeaderState::Break;
+                Err(nb::Error::Other(Error::Sync))
+            },
+            HeaderState::Identifier => {
+                self.state = HeaderState::Break;
+                identifier(byte).map(|_| byte).map_err(nb::Error::Other)
+            },
+        }
+    }
+}
+
+/// Collects the data bytes and the checksum of a response
+struct ResponseReceiver {
+    received: usize,
+}
+
+impl ResponseReceiver {
+    fn new() -> ResponseReceiver {
+        ResponseReceiver { received: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.received = 0;
+    }
+
+    // Completes once `data` is filled and the checksum matches
+    fn on_byte(
+        &mut self,
+        byte: u8,
+        model: ChecksumModel,
+        protected_identifier: u8,
+        data: &mut [u8],
+    ) -> nb::Result<(), Error> {
+        if self.received < data.len() {
+            data[self.received] = byte;
+            self.received += 1;
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.received = 0;
+        if byte != checksum(model, protected_identifier, data) {
+            return Err(nb::Error::Other(Error::Checksum));
+        }
+
+        Ok(())
+    }
+}
+
+/// LIN master, sending the headers of the frames
+pub struct Master<UART> {
+    rx: Rx<UART>,
+    tx: Tx<UART>,
+    model: ChecksumModel,
+    protected_identifier: u8,
+    response: ResponseReceiver,
+}
+
+/// LIN slave, answering the headers of its frames
+pub struct Slave<UART> {
+    rx: Rx<UART>,
+    tx: Tx<UART>,
+    model: ChecksumModel,
+    protected_identifier: u8,
+    header: HeaderReceiver,
+    response: ResponseReceiver,
+}
+
+macro_rules! lin {
+    ($($UARTX:ident,)+) => {
+        $(
+            impl Master<$UARTX> {
+                /// Creates a master from the halves of a UART configured with `Config::lin_break`
+                pub fn new(rx: Rx<$UARTX>, tx: Tx<$UARTX>, model: ChecksumModel) -> Self {
+                    Master {
+                        rx,
+                        tx,
+                        model,
+                        protected_identifier: 0,
+                        response: ResponseReceiver::new(),
+                    }
+                }
+
+                /// Sends the header of a frame, the response being read with `read_response`
+                pub fn send_header(&mut self, identifier: u8) -> Result<(), Error> {
+                    let protected_identifier = protected_identifier(identifier);
+
+                    nb::block!(self.tx.send_break())?;
+                    send(&mut self.rx, &mut self.tx, SYNC)?;
+                    send(&mut self.rx, &mut self.tx, protected_identifier)?;
+
+                    // The break has been read back too
+                    self.rx.clear_break();
+
+                    self.protected_identifier = protected_identifier;
+                    self.response.reset();
+
+                    Ok(())
+                }
+
+                /// Sends a frame with its response
+                pub fn write_frame(&mut self, identifier: u8, data: &[u8]) -> Result<(), Error> {
+                    check_data_length(data.len());
+
+                    self.send_header(identifier)?;
+                    let protected_identifier = self.protected_identifier;
+                    send_response(&mut self.rx, &mut self.tx, self.model, protected_identifier, data)
+                }
+
+                /// Receives the response of a slave to the last header into `data`
+                pub fn read_response(&mut self, data: &mut [u8]) -> nb::Result<(), Error> {
+                    check_data_length(data.len());
+
+                    let byte = receive(&mut self.rx)?;
+                    self.response.on_byte(byte, self.model, self.protected_identifier, data)
+                }
+
+                /// Releases the serial halves
+                pub fn free(self) -> (Rx<$UARTX>, Tx<$UARTX>) {
+                    (self.rx, self.tx)
+                }
+            }
+
+            impl Slave<$UARTX> {
+                /// Creates a slave from the halves of a UART configured with `Config::lin_break`
+                pub fn new(rx: Rx<$UARTX>, tx: Tx<$UARTX>, model: ChecksumModel) -> Self {
+                    Slave {
+                        rx,
+                        tx,
+                        model,
+                        protected_identifier: 0,
+                        header: HeaderReceiver::new(),
+                        response: ResponseReceiver::new(),
+                    }
+                }
+
+                /// Receives a header, returning the identifier of its frame
+                ///
+                /// The slave then either sends the response with `write_response` or receives it
+                /// with `read_response`; the frames of other nodes are ignored by polling this
+                /// again.
+                pub fn read_header(&mut self) -> nb::Result<u8, Error> {
+                    if self.rx.is_break_detected() {
+                        self.rx.clear_break();
+                        self.header.on_break();
+                    }
+
+                    let byte = receive(&mut self.rx)?;
+                    let protected_identifier = self.header.on_byte(byte)?;
+
+                    self.protected_identifier = protected_identifier;
+                    self.response.reset();
+
+                    Ok(protected_identifier & IDENTIFIER_MAX)
+                }
+
+                /// Sends the response to the last header
+                pub fn write_response(&mut self, data: &[u8]) -> Result<(), Error> {
+                    check_data_length(data.len());
+
+                    let protected_identifier = self.protected_identifier;
+                    send_response(&mut self.rx, &mut self.tx, self.model, protected_identifier, data)
+                }
+
+                /// Receives the response of the master or of another slave to the last header into
+                /// `data`
+                pub fn read_response(&mut self, data: &mut [u8]) -> nb::Result<(), Error> {
+                    check_data_length(data.len());
+
+                    let byte = receive(&mut self.rx)?;
+                    self.response.on_byte(byte, self.model, self.protected_identifier, data)
+                }
+
+                /// Releases the serial halves
+                pub fn free(self) -> (Rx<$UARTX>, Tx<$UARTX>) {
+                    (self.rx, self.tx)
+                }
+            }
+        )+
+    }
+}
+
+lin! {
+    UART0,
+    UART1,
+    UART2,
+}
+
+#[cfg(feature = "100lqfp")]
+lin! {
+    UART3,
+    UART4,
+}
+
+fn receive<RX>(rx: &mut RX) -> nb::Result<u8, Error>
+where
+    RX: Read<u8, Error = super::Error>,
+{
+    rx.read().map_err(|error| match error {
+        nb::Error::Other(error) => nb::Error::Other(Error::Serial(error)),
+        nb::Error::WouldBlock => nb::Error::WouldBlock,
+    })
+}
+
+// Sends a byte and reads its echo back
+fn send<RX, TX>(rx: &mut RX, tx: &mut TX, byte: u8) -> Result<(), Error>
+where
+    RX: Read<u8, Error = super::Error>,
+    TX: Write<u8, Error = super::Error>,
+{
+    nb::block!(tx.write(byte))?;
+
+    if nb::block!(rx.read())? != byte {
+        return Err(Error::Readback);
+    }
+
+    Ok(())
+}
+
+fn send_response<RX, TX>(
+    rx: &mut RX,
+    tx: &mut TX,
+    model: ChecksumModel,
+    protected_identifier: u8,
+    data: &[u8],
+) -> Result<(), Error>
+where
+    RX: Read<u8, Error = super::Error>,
+    TX: Write<u8, Error = super::Error>,
+{
+    for &byte in data {
+        send(rx, tx, byte)?;
+    }
+
+    send(rx, tx, checksum(model, protected_identifier, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use nb;
+
+    use super::{
+        checksum, identifier, protected_identifier, ChecksumModel, Error, HeaderReceiver,
+        ResponseReceiver, SYNC,
+    };
+
+    #[test]
+    fn protected_identifier_adds_parity_bits() {
+        assert_eq!(protected_identifier(0x00), 0x80);
+        assert_eq!(protected_identifier(0x01), 0xC1);
+        assert_eq!(protected_identifier(0x10), 0x50);
+        assert_eq!(protected_identifier(0x3C), 0x3C);
+        assert_eq!(protected_identifier(0x3D), 0x7D);
+        assert_eq!(protected_identifier(0x3F), 0xBF);
+    }
+
+    #[test]
+    fn identifier_checks_parity_bits() {
+        for id in 0..0x40 {
+            assert_eq!(identifier(protected_identifier(id)), Ok(id));
+            assert_eq!(identifier(protected_identifier(id) ^ 0x40), Err(Error::Identifier));
+            assert_eq!(identifier(protected_identifier(id) ^ 0x80), Err(Error::Identifier));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn protected_identifier_rejects_large_identifiers() {
+        protected_identifier(0x40);
+    }
+
+    #[test]
+    fn checksum_sums_with_carry() {
+        let data = [0x55, 0x93, 0xE5];
+
+        assert_eq!(checksum(ChecksumModel::Enhanced, 0x4A, &data), 0xE6);
+        assert_eq!(checksum(ChecksumModel::Classic, 0x4A, &data), 0x31);
+        assert_eq!(checksum(ChecksumModel::Classic, 0x80, &[0xFF, 0xFF]), 0x00);
+        assert_eq!(checksum(ChecksumModel::Classic, 0x80, &[0x00]), 0xFF);
+    }
+
+    #[test]
+    fn checksum_of_diagnostic_frames_is_classic() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+        for &id in &[0x3C, 0x3D] {
+            let pid = protected_identifier(id);
+            assert_eq!(
+                checksum(ChecksumModel::Enhanced, pid, &data),
+                checksum(ChecksumModel::Classic, pid, &data)
+            );
+        }
+    }
+
+    #[test]
+    fn header_receiver_decodes_headers() {
+        let mut header = HeaderReceiver::new();
+
+        // A response to another node
+        assert_eq!(header.on_byte(0x12), Err(nb::Error::WouldBlock));
+
+        header.on_break();
+        assert_eq!(header.on_byte(SYNC), Err(nb::Error::WouldBlock));
+        assert_eq!(header.on_byte(0x50), Ok(0x50));
+
+        // The response following the header
+        assert_eq!(header.on_byte(SYNC), Err(nb::Error::WouldBlock));
+        assert_eq!(header.on_byte(0x50), Err(nb::Error::WouldBlock));
+    }
+
+    #[test]
+    fn header_receiver_rejects_bad_headers() {
+        let mut header = HeaderReceiver::new();
+
+        header.on_break();
+        assert_eq!(header.on_byte(0x54), Err(nb::Error::Other(Error::Sync)));
+
+        header.on_break();
+        assert_eq!(header.on_byte(SYNC), Err(nb::Error::WouldBlock));
+        assert_eq!(header.on_byte(0x10), Err(nb::Error::Other(Error::Identifier)));
+    }
+
+    #[test]
+    fn response_receiver_checks_checksum() {
+        let mut response = ResponseReceiver::new();
+        let mut data = [0; 3];
+
+        for &byte in &[0x55, 0x93, 0xE5] {
+            assert_eq!(
+                response.on_byte(byte, ChecksumModel::Enhanced, 0x4A, &mut data),
+                Err(nb::Error::WouldBlock)
+            );
+        }
+        assert_eq!(response.on_byte(0xE6, ChecksumModel::Enhanced, 0x4A, &mut data), Ok(()));
+        assert_eq!(data, [0x55, 0x93, 0xE5]);
+
+        for &byte in &[0x55, 0x93, 0xE5] {
+            assert_eq!(
+                response.on_byte(byte, ChecksumModel::Enhanced, 0x4A, &mut data),
+                Err(nb::Error::WouldBlock)
+            );
+        }
+        assert_eq!(
+            response.on_byte(0x30, ChecksumModel::Enhanced, 0x4A, &mut data),
+            Err(nb::Error::Other(Error::Checksum))
+        );
+    }
+}
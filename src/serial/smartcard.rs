@@ -0,0 +1,927 @@
+//! ISO-7816 smart card interface on UART0
+//!
+//! The card I/O line is the single wire of the UART0 TX pin, which has to be open drain with a
+//! pull-up. The card clock and the reset line are driven separately, e.g. by an FTM channel and a
+//! GPIO pin. After the reset is released the card sends its answer to reset, read with
+//! `SmartCard::read_atr`; the commands are then exchanged with `transmit_t0` or with the T=1
+//! blocks of `write_block` and `read_block`.
+//!
+//! With the T=0 protocol the UART answers the characters received with a parity error with a
+//! NACK, and sends again the characters the card answered with a NACK. Extended length APDUs, the
+//! PPS exchange and the chaining of T=1 blocks are left to the application.
+
+use core::marker::PhantomData;
+
+use bitrate::{Bps, Hertz};
+use hal::serial::{Read, Write};
+use mk20d7::{self, UART0};
+use nb;
+
+use bitband;
+use sim::Clocks;
+use super::{ControlRegisters, Mode, Rx, Serial, Tx, TxPin};
+
+/// Largest length of an answer to reset
+pub const ATR_LENGTH_MAX: usize = 33;
+
+/// Largest length of the information field of a T=1 block
+pub const INFORMATION_LENGTH_MAX: usize = 254;
+
+/// Initial character of a card using the direct convention
+const DIRECT_CONVENTION: u8 = 0x3B;
+
+/// Initial character of a card using the inverse convention, as read once it is detected
+const INVERSE_CONVENTION: u8 = 0x3F;
+
+/// Procedure byte asking the reader to keep waiting
+const NULL: u8 = 0x60;
+
+/// Clock rate conversion integers Fi, indexed by the high nibble of TA1
+const CLOCK_RATE_CONVERSIONS: [u16; 16] =
+    [372, 372, 558, 744, 1116, 1488, 1860, 0, 0, 512, 768, 1024, 1536, 2048, 0, 0];
+
+/// Baud rate adjustment integers Di, indexed by the low nibble of TA1
+const BAUD_RATE_ADJUSTMENTS: [u8; 16] = [0, 1, 2, 4, 8, 16, 32, 64, 12, 20, 0, 0, 0, 0, 0, 0];
+
+/// Transmission protocol
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Protocol {
+    /// Character oriented protocol
+    T0,
+
+    /// Block oriented protocol
+    T1,
+}
+
+/// Encoding of the characters, given by the initial character of the answer to reset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Convention {
+    /// LSB first, high level is 1
+    Direct,
+
+    /// MSB first, low level is 1
+    Inverse,
+}
+
+/// Smart card error
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// UART error
+    Serial(super::Error),
+
+    /// F or D is 0, or the retries, CWI or BWI of the configuration are above 15
+    InvalidConfig,
+
+    /// The answer to reset is malformed
+    Atr,
+
+    /// The TCK of the answer to reset or the LRC of a block is wrong
+    Checksum,
+
+    /// The card sent a NACK for a character more often than `Config::transmit_retries`
+    TransmitThreshold,
+
+    /// More characters than `Config::receive_retries` were received with a parity error
+    ReceiveThreshold,
+
+    /// The card sent a character before the end of the guard time
+    GuardTime,
+
+    /// The card did not send a character within the T=0 waiting time
+    WaitingTime,
+
+    /// The card did not send the next character of a block within the character waiting time
+    CharacterWaitingTime,
+
+    /// The card did not start a block within the block waiting time
+    BlockWaitingTime,
+
+    /// The card sent an unexpected T=0 procedure byte
+    ProcedureByte,
+
+    /// The information field of a T=1 block does not fit in the buffer
+    BlockLength,
+
+    /// The P3 byte of a T=0 header does not match the length of the data
+    DataLength,
+
+    /// The card sent more T=0 response bytes than fit in the buffer
+    ResponseLength,
+}
+
+impl From<super::Error> for Error {
+    fn from(error: super::Error) -> Error {
+        Error::Serial(error)
+    }
+}
+
+/// Smart card configuration
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Frequency of the clock driving the card
+    pub card_clock: Hertz<u32>,
+
+    /// Transmission protocol
+    pub protocol: Protocol,
+
+    /// Clock rate conversion integer F, the number of card clock cycles of an elementary time
+    /// unit when D is 1
+    pub clock_rate_conversion: u16,
+
+    /// Baud rate adjustment integer D
+    pub baud_rate_adjustment: u8,
+
+    /// Extra guard time N, in elementary time units added between the characters sent to the
+    /// card; 255 is the minimal guard time
+    pub extra_guard_time: u8,
+
+    /// Answers the characters received with a parity error with a NACK, T=0 only
+    pub nack_on_error: bool,
+
+    /// Answers the characters received while the receiver is full with a NACK, T=0 only
+    pub nack_on_overflow: bool,
+
+    /// Number of NACKs accepted for a character before `Error::TransmitThreshold`, up to 15
+    pub transmit_retries: u8,
+
+    /// Number of characters received with a parity error before `Error::ReceiveThreshold`, up
+    /// to 15
+    pub receive_retries: u8,
+
+    /// Work waiting integer WI of T=0
+    pub work_waiting_integer: u8,
+
+    /// Character waiting integer CWI of T=1, up to 15
+    pub character_waiting_integer: u8,
+
+    /// Block waiting integer BWI of T=1, up to 15
+    pub block_waiting_integer: u8,
+}
+
+impl Config {
+    /// Default parameters of ISO-7816-3 for a card driven by `card_clock`: T=0, F = 372, D = 1,
+    /// N = 0, WI = 10, CWI = 13, BWI = 4, NACK on parity errors and 3 retries
+    pub fn new(card_clock: Hertz<u32>) -> Config {
+        Config {
+            card_clock,
+            protocol: Protocol::T0,
+            clock_rate_conversion: 372,
+            baud_rate_adjustment: 1,
+            extra_guard_time: 0,
+            nack_on_error: true,
+            nack_on_overflow: false,
+            transmit_retries: 3,
+            receive_retries: 3,
+            work_waiting_integer: 10,
+            character_waiting_integer: 13,
+            block_waiting_integer: 4,
+        }
+    }
+
+    /// Sets the transmission protocol
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Sets the clock rate conversion integer F and the baud rate adjustment integer D
+    pub fn rate(mut self, clock_rate_conversion: u16, baud_rate_adjustment: u8) -> Self {
+        self.clock_rate_conversion = clock_rate_conversion;
+        self.baud_rate_adjustment = baud_rate_adjustment;
+        self
+    }
+
+    /// Sets the extra guard time N
+    pub fn extra_guard_time(mut self, extra_guard_time: u8) -> Self {
+        self.extra_guard_time = extra_guard_time;
+        self
+    }
+
+    /// Sets whether the received characters with a parity error or an overrun are answered with
+    /// a NACK
+    pub fn nack(mut self, on_error: bool, on_overflow: bool) -> Self {
+        self.nack_on_error = on_error;
+        self.nack_on_overflow = on_overflow;
+        self
+    }
+
+    /// Sets the number of retries of the transmitter and of the receiver
+    pub fn retries(mut self, transmit: u8, receive: u8) -> Self {
+        self.transmit_retries = transmit;
+        self.receive_retries = receive;
+        self
+    }
+
+    /// Sets the work waiting integer WI of T=0
+    pub fn work_waiting_integer(mut self, work_waiting_integer: u8) -> Self {
+        self.work_waiting_integer = work_waiting_integer;
+        self
+    }
+
+    /// Sets the character and block waiting integers CWI and BWI of T=1
+    pub fn waiting_integers(mut self, character: u8, block: u8) -> Self {
+        self.character_waiting_integer = character;
+        self.block_waiting_integer = block;
+        self
+    }
+}
+
+/// Answer to reset
+#[derive(Clone, Copy)]
+pub struct Atr {
+    bytes: [u8; ATR_LENGTH_MAX],
+    length: usize,
+}
+
+impl Atr {
+    /// Returns the bytes of the answer to reset, from the initial character to TCK
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+
+    /// Returns the convention of the card
+    pub fn convention(&self) -> Convention {
+        if self.bytes[0] == INVERSE_CONVENTION {
+            Convention::Inverse
+        } else {
+            Convention::Direct
+        }
+    }
+
+    /// Returns the historical bytes
+    pub fn historical_bytes(&self) -> &[u8] {
+        let count = usize::from(self.bytes[1] & 0x0F);
+        let end = interface_bytes(self.bytes(), |_, _, _| {}).map_or(0, |(end, _)| end);
+
+        &self.bytes[end..end + count]
+    }
+
+    /// Returns the first protocol offered by the card, `None` if it is neither T=0 nor T=1
+    pub fn protocol(&self) -> Option<Protocol> {
+        match self.interface_byte(1, INTERFACE_TD).map_or(0, |td| td & 0x0F) {
+            0 => Some(Protocol::T0),
+            1 => Some(Protocol::T1),
+            _ => None,
+        }
+    }
+
+    /// Returns the clock rate conversion integer Fi of TA1, `None` if it is reserved
+    pub fn clock_rate_conversion(&self) -> Option<u16> {
+        match self.interface_byte(1, INTERFACE_TA) {
+            Some(ta1) => Some(CLOCK_RATE_CONVERSIONS[usize::from(ta1 >> 4)]).filter(|&f| f != 0),
+            None => Some(372),
+        }
+    }
+
+    /// Returns the baud rate adjustment integer Di of TA1, `None` if it is reserved
+    pub fn baud_rate_adjustment(&self) -> Option<u8> {
+        match self.interface_byte(1, INTERFACE_TA) {
+            Some(ta1) => Some(BAUD_RATE_ADJUSTMENTS[usize::from(ta1 & 0x0F)]).filter(|&d| d != 0),
+            None => Some(1),
+        }
+    }
+
+    /// Returns `true` if the card imposes Fi and Di with TA2, instead of negotiating them
+    pub fn is_specific_mode(&self) -> bool {
+        self.interface_byte(2, INTERFACE_TA).is_some()
+    }
+
+    /// Returns the extra guard time N of TC1
+    pub fn extra_guard_time(&self) -> u8 {
+        self.interface_byte(1, INTERFACE_TC).unwrap_or(0)
+    }
+
+    /// Returns the work waiting integer WI of TC2
+    pub fn work_waiting_integer(&self) -> u8 {
+        self.interface_byte(2, INTERFACE_TC).unwrap_or(10)
+    }
+
+    /// Returns the information field size of the card IFSC, from the first TA for T=1
+    pub fn information_field_size(&self) -> u8 {
+        self.t1_interface_byte(INTERFACE_TA).unwrap_or(32)
+    }
+
+    /// Returns the character waiting integer CWI, from the first TB for T=1
+    pub fn character_waiting_integer(&self) -> u8 {
+        self.t1_interface_byte(INTERFACE_TB).map_or(13, |tb| tb & 0x0F)
+    }
+
+    /// Returns the block waiting integer BWI, from the first TB for T=1
+    pub fn block_waiting_integer(&self) -> u8 {
+        self.t1_interface_byte(INTERFACE_TB).map_or(4, |tb| tb >> 4)
+    }
+
+    fn interface_byte(&self, group: usize, kind: usize) -> Option<u8> {
+        let mut found = None;
+        interface_bytes(self.bytes(), |g, k, byte| if g == group && k == kind {
+            found = Some(byte);
+        });
+
+        found
+    }
+
+    // The T=1 parameters are in the groups from 3 on following a TD for T=1
+    fn t1_interface_byte(&self, kind: usize) -> Option<u8> {
+        let mut found = None;
+        let mut protocol = 0;
+        interface_bytes(self.bytes(), |group, k, byte| {
+            if group >= 3 && protocol == 1 && k == kind && found.is_none() {
+                found = Some(byte);
+            }
+            if k == INTERFACE_TD {
+                protocol = byte & 0x0F;
+            }
+        });
+
+        found
+    }
+}
+
+const INTERFACE_TA: usize = 0;
+const INTERFACE_TB: usize = 1;
+const INTERFACE_TC: usize = 2;
+const INTERFACE_TD: usize = 3;
+
+// Visits the interface bytes of an answer to reset with their group, from 1, and their kind,
+// returning the index of the historical bytes and whether a TCK follows them, or `None` if
+// `bytes` ends before
+fn interface_bytes<F>(bytes: &[u8], mut visit: F) -> Option<(usize, bool)>
+where
+    F: FnMut(usize, usize, u8),
+{
+    let mut indicator = *bytes.get(1)? >> 4;
+    let mut index = 2;
+    let mut group = 1;
+    let mut checksum = false;
+
+    loop {
+        let mut td = None;
+        for kind in INTERFACE_TA..=INTERFACE_TD {
+            if indicator & 1 << kind != 0 {
+                let byte = *bytes.get(index)?;
+                visit(group, kind, byte);
+                index += 1;
+
+                if kind == INTERFACE_TD {
+                    td = Some(byte);
+                }
+            }
+        }
+
+        match td {
+            // TCK is present unless T=0 is the only protocol offered
+            Some(td) => {
+                checksum |= td & 0x0F != 0;
+                indicator = td >> 4;
+                group += 1;
+            },
+            None => return Some((index, checksum)),
+        }
+    }
+}
+
+/// Collects the bytes of an answer to reset
+struct AtrReceiver {
+    atr: Atr,
+}
+
+impl AtrReceiver {
+    fn new() -> AtrReceiver {
+        AtrReceiver { atr: Atr { bytes: [0; ATR_LENGTH_MAX], length: 0 } }
+    }
+
+    fn reset(&mut self) {
+        self.atr.length = 0;
+    }
+
+    // Completes once the last byte announced by the interface bytes is received
+    fn on_byte(&mut self, byte: u8) -> nb::Result<Atr, Error> {
+        let atr = &mut self.atr;
+
+        if atr.length == 0 && byte != DIRECT_CONVENTION && byte != INVERSE_CONVENTION {
+            return Err(nb::Error::Other(Error::Atr));
+        }
+
+        if atr.length == ATR_LENGTH_MAX {
+            atr.length = 0;
+            return Err(nb::Error::Other(Error::Atr));
+        }
+
+        atr.bytes[atr.length] = byte;
+        atr.length += 1;
+
+        let (end, checksum) = match interface_bytes(atr.bytes(), |_, _, _| {}) {
+            Some(structure) => structure,
+            None => return Err(nb::Error::WouldBlock),
+        };
+
+        let length = end + usize::from(atr.bytes[1] & 0x0F) + usize::from(checksum);
+        if length > ATR_LENGTH_MAX {
+            atr.length = 0;
+            return Err(nb::Error::Other(Error::Atr));
+        }
+
+        if atr.length < length {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // The exclusive or of T0 to TCK is 0
+        if checksum && atr.bytes[1..length].iter().fold(0, |lrc, byte| lrc ^ byte) != 0 {
+            atr.length = 0;
+            return Err(nb::Error::Other(Error::Checksum));
+        }
+
+        Ok(*atr)
+    }
+}
+
+/// Step of a T=0 command given by a procedure byte
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Procedure {
+    /// The card needs more time
+    Null,
+
+    /// The remaining data bytes are exchanged
+    All,
+
+    /// The next data byte is exchanged
+    One,
+
+    /// The procedure byte is SW1, SW2 follows
+    Status,
+}
+
+fn procedure(instruction: u8, byte: u8) -> Result<Procedure, Error> {
+    match byte {
+        NULL => Ok(Procedure::Null),
+        _ if byte == instruction => Ok(Procedure::All),
+        _ if byte == !instruction => Ok(Procedure::One),
+        0x61..=0x6F | 0x90..=0x9F => Ok(Procedure::Status),
+        _ => Err(Error::ProcedureByte),
+    }
+}
+
+/// Returns the longitudinal redundancy check of a T=1 block, the exclusive or of its bytes
+pub fn lrc(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |lrc, byte| lrc ^ byte)
+}
+
+/// Prologue of a received T=1 block
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Block {
+    /// Node address byte
+    pub nad: u8,
+
+    /// Protocol control byte
+    pub pcb: u8,
+
+    /// Length of the information field
+    pub length: usize,
+}
+
+/// Smart card interface
+pub struct SmartCard<PIN> {
+    uart: UART0,
+    pin: PIN,
+    config: Config,
+    convention: Convention,
+    baud_rate: Bps<u32>,
+    atr: AtrReceiver,
+}
+
+impl<PIN> SmartCard<PIN> {
+    /// Configures UART0 for a smart card on the single wire of its TX pin
+    pub fn uart0(uart: UART0, pin: PIN, config: Config, clocks: &Clocks) -> Result<Self, Error>
+    where
+        PIN: TxPin<UART0>,
+    {
+        let baud_rate = Self::configure(&uart, &config, Convention::Direct, clocks)?;
+
+        Ok(SmartCard {
+            uart,
+            pin,
+            config,
+            convention: Convention::Direct,
+            baud_rate,
+            atr: AtrReceiver::new(),
+        })
+    }
+
+    // Reference: 47.8.4 Initialization sequence (ISO-7816)
+    fn configure(
+        uart: &UART0,
+        config: &Config,
+        convention: Convention,
+        clocks: &Clocks,
+    ) -> Result<Bps<u32>, Error> {
+        if config.clock_rate_conversion == 0 ||
+            config.baud_rate_adjustment == 0 ||
+            config.transmit_retries > 15 ||
+            config.receive_retries > 15 ||
+            config.character_waiting_integer > 15 ||
+            config.block_waiting_integer > 15
+        {
+            return Err(Error::InvalidConfig);
+        }
+
+        // The 7816 registers are only written while the transmitter and the receiver are
+        // disabled
+        // Reference: 47.3.4 UART Control Register 2 (UART_C2)
+        uart.c2.write(|w| w);
+
+        let t1 = config.protocol == Protocol::T1;
+
+        // Reference: 47.3.23 UART 7816 Control Register (UART_C7816)
+        uart.c7816.write(|w| {
+            w.iso_7816e().set_bit();
+            w.ttype().bit(t1);
+            w.anack().bit(config.nack_on_error && !t1);
+            w.onack().bit(config.nack_on_overflow && !t1)
+        });
+
+        if t1 {
+            // Reference: 47.3.27 UART 7816 Wait Parameter Register (UART_WP7816T1)
+            // WP7816T1 is the T=1 view of WP7816T0
+            let wp7816t1 = unsafe {
+                &*(&uart.wp7816t0 as *const _ as *const mk20d7::uart0::WP7816T1)
+            };
+            wp7816t1.write(|w| unsafe {
+                w.cwi().bits(config.character_waiting_integer);
+                w.bwi().bits(config.block_waiting_integer)
+            });
+        } else {
+            // Reference: 47.3.26 UART 7816 Wait Parameter Register (UART_WP7816T0)
+            uart.wp7816t0.write(|w| unsafe { w.wi().bits(config.work_waiting_integer) });
+        }
+
+        // Reference: 47.3.28 UART 7816 Wait N Register (UART_WN7816)
+        uart.wn7816.write(|w| unsafe { w.gtn().bits(config.extra_guard_time) });
+
+        // Reference: 47.3.29 UART 7816 Wait FD Register (UART_WF7816)
+        // The waiting times are counted in elementary time units of the default rate, times D
+        uart.wf7816.write(|w| unsafe { w.gtfd().bits(config.baud_rate_adjustment) });
+
+        // Reference: 47.3.30 UART 7816 Error Threshold Register (UART_ET7816)
+        uart.et7816.write(|w| unsafe {
+            w.txthreshold().bits(config.transmit_retries);
+            w.rxthreshold().bits(config.receive_retries)
+        });
+
+        // An elementary time unit lasts F / D card clock cycles
+        let baud_rate = u64::from(config.card_clock.0) * u64::from(config.baud_rate_adjustment) /
+            u64::from(config.clock_rate_conversion);
+
+        // 8 data bits and an even parity bit
+        let serial_config = super::Config::default().baud_rate(Bps(baud_rate as u32)).parity_even();
+        let serial_config = match convention {
+            Convention::Direct => serial_config,
+            Convention::Inverse => serial_config.msb_first().invert_rx(true).invert_tx(true),
+        };
+
+        Ok(Serial::<UART0, PIN>::configure(uart, &serial_config, clocks, Mode::SingleWire)?)
+    }
+
+    /// Returns the baud rate actually generated from the module clock
+    pub fn baud_rate(&self) -> Bps<u32> {
+        self.baud_rate
+    }
+
+    /// Returns the configuration in use
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Prepares the reception of the answer to reset, to be called before releasing the reset of
+    /// the card
+    ///
+    /// The convention of the card is detected from the initial character.
+    pub fn activate(&mut self) {
+        self.set_transmit(false);
+        self.atr.reset();
+
+        // Reference: 47.3.25 UART 7816 Interrupt Status Register (UART_IS7816)
+        self.uart.is7816.write(|w| unsafe { w.bits(0xFF) });
+
+        // Reference: 47.3.23 UART 7816 Control Register (UART_C7816)
+        self.uart.c7816.modify(|_, w| w.init().set_bit());
+    }
+
+    /// Receives the answer to reset
+    pub fn read_atr(&mut self) -> nb::Result<Atr, Error> {
+        let byte = self.read()?;
+
+        // Reference: 47.3.6 UART Status Register 2 (UART_S2)
+        // The detection of the initial character has set MSBF and RXINV for the convention
+        if self.atr.atr.length == 0 {
+            self.convention = if self.uart.s2.read().msbf().bit_is_set() {
+                Convention::Inverse
+            } else {
+                Convention::Direct
+            };
+        }
+
+        self.atr.on_byte(byte)
+    }
+
+    /// Applies the parameters of the answer to reset: the protocol, the guard time and the
+    /// waiting integers, and Fi and Di when the card is in specific mode
+    pub fn apply_atr(&mut self, atr: &Atr, clocks: &Clocks) -> Result<(), Error> {
+        let mut config = self.config;
+
+        if let Some(protocol) = atr.protocol() {
+            config.protocol = protocol;
+        }
+        config.extra_guard_time = atr.extra_guard_time();
+        config.work_waiting_integer = atr.work_waiting_integer();
+        config.character_waiting_integer = atr.character_waiting_integer();
+        config.block_waiting_integer = atr.block_waiting_integer();
+
+        if atr.is_specific_mode() {
+            match (atr.clock_rate_conversion(), atr.baud_rate_adjustment()) {
+                (Some(f), Some(d)) => config = config.rate(f, d),
+                _ => return Err(Error::Atr),
+            }
+        }
+
+        self.baud_rate = Self::configure(&self.uart, &config, self.convention, clocks)?;
+        self.config = config;
+
+        Ok(())
+    }
+
+    /// Exchanges a T=0 command: the 5 bytes header, then either `data` sent to the card or the
+    /// response received in `response`, returning the length of the response and the status word.
+    /// When `data` is not empty, P3 must be its length.
+    pub fn transmit_t0(
+        &mut self,
+        header: &[u8; 5],
+        data: &[u8],
+        response: &mut [u8],
+    ) -> Result<(usize, u16), Error> {
+        let instruction = header[1];
+
+        // P3 is the length of the data, 0 meaning 256 bytes of response
+        let length = if data.is_empty() {
+            match header[4] {
+                0 => 256,
+                p3 => usize::from(p3),
+            }
+        } else if usize::from(header[4]) == data.len() {
+            data.len()
+        } else {
+            return Err(Error::DataLength);
+        };
+
+        for &byte in header {
+            nb::block!(self.write(byte))?;
+        }
+
+        let mut transferred = 0;
+        loop {
+            let byte = nb::block!(self.read())?;
+
+            let count = match procedure(instruction, byte)? {
+                Procedure::Null => continue,
+                Procedure::All => length - transferred,
+                Procedure::One => 1,
+                Procedure::Status => {
+                    let sw2 = nb::block!(self.read())?;
+                    let received = if data.is_empty() { transferred } else { 0 };
+                    return Ok((received, u16::from(byte) << 8 | u16::from(sw2)));
+                },
+            };
+
+            if transferred + count > length {
+                return Err(Error::ProcedureByte);
+            }
+            if data.is_empty() && transferred + count > response.len() {
+                return Err(Error::ResponseLength);
+            }
+
+            for _ in 0..count {
+                if data.is_empty() {
+                    response[transferred] = nb::block!(self.read())?;
+                } else {
+                    nb::block!(self.write(data[transferred]))?;
+                }
+                transferred += 1;
+            }
+        }
+    }
+
+    /// Sends a T=1 block with an LRC epilogue
+    pub fn write_block(&mut self, nad: u8, pcb: u8, information: &[u8]) -> Result<(), Error> {
+        if information.len() > INFORMATION_LENGTH_MAX {
+            panic!("Invalid T=1 information field length: {}", information.len());
+        }
+
+        let length = information.len() as u8;
+
+        // Reference: 47.3.31 UART 7816 Transmit Length Register (UART_TL7816)
+        // The block waiting time starts once the characters of the block have been sent
+        self.uart.tl7816.write(|w| unsafe { w.tlen().bits(length) });
+
+        let prologue = [nad, pcb, length];
+        for &byte in prologue.iter().chain(information) {
+            nb::block!(self.write(byte))?;
+        }
+
+        nb::block!(self.write(lrc(&prologue) ^ lrc(information)))?;
+        nb::block!(self.flush())
+    }
+
+    /// Receives a T=1 block with an LRC epilogue, its information field going to `information`
+    pub fn read_block(&mut self, information: &mut [u8]) -> Result<Block, Error> {
+        let nad = nb::block!(self.read())?;
+        let pcb = nb::block!(self.read())?;
+        let length = nb::block!(self.read())?;
+
+        let block = Block { nad, pcb, length: usize::from(length) };
+        if block.length > information.len() {
+            return Err(Error::BlockLength);
+        }
+
+        for byte in &mut information[..block.length] {
+            *byte = nb::block!(self.read())?;
+        }
+
+        let epilogue = nb::block!(self.read())?;
+        if lrc(&[nad, pcb, length, epilogue]) != lrc(&information[..block.length]) {
+            return Err(Error::Checksum);
+        }
+
+        Ok(block)
+    }
+
+    /// Disables the ISO-7816 mode and releases the UART peripheral and the pin
+    pub fn free(self) -> (UART0, PIN) {
+        self.uart.c2.write(|w| w);
+        self.uart.c7816.write(|w| w);
+
+        (self.uart, self.pin)
+    }
+
+    // Reference: 47.3.7 UART Control Register 3 (UART_C3)
+    fn set_transmit(&mut self, transmit: bool) {
+        unsafe { bitband::write(self.uart.c3(), 5, transmit) };
+    }
+
+    // Reference: 47.3.25 UART 7816 Interrupt Status Register (UART_IS7816)
+    // The flags are cleared by writing 1
+    fn check_receive_errors(&self) -> Result<(), Error> {
+        let is7816 = self.uart.is7816.read();
+
+        if is7816.rxt().bit_is_set() {
+            self.uart.is7816.write(|w| w.rxt().set_bit());
+            Err(Error::ReceiveThreshold)
+        } else if is7816.gtv().bit_is_set() {
+            self.uart.is7816.write(|w| w.gtv().set_bit());
+            Err(Error::GuardTime)
+        } else if is7816.wt().bit_is_set() {
+            self.uart.is7816.write(|w| w.wt().set_bit());
+            Err(Error::WaitingTime)
+        } else if is7816.cwt().bit_is_set() {
+            self.uart.is7816.write(|w| w.cwt().set_bit());
+            Err(Error::CharacterWaitingTime)
+        } else if is7816.bwt().bit_is_set() {
+            self.uart.is7816.write(|w| w.bwt().set_bit());
+            Err(Error::BlockWaitingTime)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<PIN> Read<u8> for SmartCard<PIN> {
+    type Error = Error;
+
+    /// Reads a character, the pin listening to the wire once the last character has been sent
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        if self.uart.c3.read().txdir().bit_is_set() {
+            nb::block!(self.flush())?;
+            self.set_transmit(false);
+        }
+
+        self.check_receive_errors()?;
+
+        let mut rx = Rx::<UART0> { _uart: PhantomData };
+        Read::<u8>::read(&mut rx).map_err(|error| error.map(Error::Serial))
+    }
+}
+
+impl<PIN> Write<u8> for SmartCard<PIN> {
+    type Error = Error;
+
+    fn flush(&mut self) -> nb::Result<(), Error> {
+        let mut tx = Tx::<UART0>::new();
+        Write::<u8>::flush(&mut tx).map_err(|error| error.map(Error::Serial))
+    }
+
+    /// Writes a character, the pin driving the wire
+    fn write(&mut self, byte: u8) -> nb::Result<(), Error> {
+        // Reference: 47.3.25 UART 7816 Interrupt Status Register (UART_IS7816)
+        if self.uart.is7816.read().txt().bit_is_set() {
+            self.uart.is7816.write(|w| w.txt().set_bit());
+            return Err(nb::Error::Other(Error::TransmitThreshold));
+        }
+
+        self.set_transmit(true);
+
+        let mut tx = Tx::<UART0>::new();
+        Write::<u8>::write(&mut tx, byte).map_err(|error| error.map(Error::Serial))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nb;
+
+    use super::{lrc, procedure, Atr, AtrReceiver, Convention, Error, Procedure, Protocol};
+
+    fn receive(bytes: &[u8]) -> Result<Atr, Error> {
+        let mut receiver = AtrReceiver::new();
+
+        for (index, &byte) in bytes.iter().enumerate() {
+            match receiver.on_byte(byte) {
+                Ok(atr) => {
+                    assert_eq!(index, bytes.len() - 1);
+                    return Ok(atr);
+                },
+                Err(nb::Error::WouldBlock) => assert!(index < bytes.len() - 1),
+                Err(nb::Error::Other(error)) => return Err(error),
+            }
+        }
+
+        panic!("Incomplete ATR");
+    }
+
+    #[test]
+    fn atr_of_t0_card() {
+        // TA1 = 0x95: Fi = 512, Di = 16; TC1 = 0x00; TD1 = 0x80, TD2 = 0x00 (T=0 only, no TCK)
+        let atr = receive(&[0x3B, 0xD5, 0x95, 0x00, 0x80, 0x00, 1, 2, 3, 4, 5]).unwrap();
+
+        assert_eq!(atr.convention(), Convention::Direct);
+        assert_eq!(atr.protocol(), Some(Protocol::T0));
+        assert_eq!(atr.clock_rate_conversion(), Some(512));
+        assert_eq!(atr.baud_rate_adjustment(), Some(16));
+        assert_eq!(atr.extra_guard_time(), 0);
+        assert_eq!(atr.work_waiting_integer(), 10);
+        assert!(!atr.is_specific_mode());
+        assert_eq!(atr.historical_bytes(), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn atr_of_t1_card() {
+        // TD1 = 0x81: T=1, TD2 follows; TD2 = 0x31: TA3 and TB3 for T=1
+        let mut bytes = [0x3B, 0x82, 0x81, 0x31, 0xFE, 0x45, 0xAA, 0xBB, 0x00];
+        let tck = bytes[1..8].iter().fold(0, |tck, byte| tck ^ byte);
+        bytes[8] = tck;
+
+        let atr = receive(&bytes).unwrap();
+
+        assert_eq!(atr.bytes(), &bytes);
+        assert_eq!(atr.protocol(), Some(Protocol::T1));
+        assert_eq!(atr.clock_rate_conversion(), Some(372));
+        assert_eq!(atr.baud_rate_adjustment(), Some(1));
+        assert_eq!(atr.information_field_size(), 0xFE);
+        assert_eq!(atr.block_waiting_integer(), 4);
+        assert_eq!(atr.character_waiting_integer(), 5);
+        assert_eq!(atr.historical_bytes(), &[0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn atr_checks_tck() {
+        let bytes = [0x3B, 0x80, 0x01, 0x00];
+
+        assert_eq!(receive(&bytes).err(), Some(Error::Checksum));
+    }
+
+    #[test]
+    fn atr_checks_initial_character() {
+        assert_eq!(receive(&[0x12]).err(), Some(Error::Atr));
+    }
+
+    #[test]
+    fn atr_of_inverse_convention_card() {
+        let atr = receive(&[0x3F, 0x00]).unwrap();
+
+        assert_eq!(atr.convention(), Convention::Inverse);
+        assert!(atr.historical_bytes().is_empty());
+    }
+
+    #[test]
+    fn procedure_bytes() {
+        assert_eq!(procedure(0xA4, 0x60), Ok(Procedure::Null));
+        assert_eq!(procedure(0xA4, 0xA4), Ok(Procedure::All));
+        assert_eq!(procedure(0xA4, 0x5B), Ok(Procedure::One));
+        assert_eq!(procedure(0xA4, 0x90), Ok(Procedure::Status));
+        assert_eq!(procedure(0xA4, 0x6C), Ok(Procedure::Status));
+        assert_eq!(procedure(0xA4, 0x12), Err(Error::ProcedureByte));
+    }
+
+    #[test]
+    fn lrc_is_exclusive_or() {
+        assert_eq!(lrc(&[]), 0);
+        assert_eq!(lrc(&[0x00, 0x40, 0x02, 0x12, 0x34]), 0x64);
+    }
+}
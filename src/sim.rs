@@ -1,7 +1,21 @@
 use mk20d7::{sim::RegisterBlock, sim::clkdiv1};
+use bitrate::{Hertz, U32BitrateExt};
 
 pub const MAXIMUM_CLOCK_FREQUENCY: u8 = 72;
 
+/// Frequencies of the system clocks, derived from the MCG output clock
+#[derive(Clone, Copy)]
+pub struct Clocks {
+    /// Core and system clock, which also drives UART0 and UART1
+    pub core: Hertz<u32>,
+
+    /// Bus clock, which drives the other peripherals
+    pub bus: Hertz<u32>,
+
+    /// Flash clock
+    pub flash: Hertz<u32>,
+}
+
 pub struct SystemIntegrationModule<'a> {
     sim: &'a RegisterBlock,
 }
@@ -159,4 +173,14 @@ impl<'a> SystemIntegrationModule<'a> {
             MAXIMUM_CLOCK_FREQUENCY / flash,
         )
     }
+
+    // Reference: 5.4 Clock definitions
+    pub fn get_clocks(&self, mcg_output_frequency: Hertz<u32>) -> Clocks {
+        let (core, bus, flash) = self.get_dividers();
+        Clocks {
+            core: (mcg_output_frequency.0 / u32::from(core)).hz(),
+            bus: (mcg_output_frequency.0 / u32::from(bus)).hz(),
+            flash: (mcg_output_frequency.0 / u32::from(flash)).hz(),
+        }
+    }
 }
@@ -0,0 +1,359 @@
+//! Teensy 3.1 / 3.2 board support
+//!
+//! The pins of the board are named after their number on the silkscreen, e.g. `Pins::p13` is
+//! `PTC5`, which drives the on-board LED.
+
+use bitrate::{MegaHertz, U32BitrateExt};
+use mk20d7::{sim::SCGC5, PORTA, PORTB, PORTC, PORTD, PORTE, PTA, PTB, PTC, PTD, PTE};
+
+use gpio::{
+    gpioa::{self, PTA0, PTA1, PTA2, PTA3, PTA4, PTA5, PTA12, PTA13, PTA18, PTA19},
+    gpiob::{self, PTB0, PTB1, PTB2, PTB3, PTB16, PTB17, PTB18, PTB19},
+    gpioc::{self, PTC0, PTC1, PTC2, PTC3, PTC4, PTC5, PTC6, PTC7, PTC8, PTC9, PTC10, PTC11},
+    gpiod::{self, PTD0, PTD1, PTD2, PTD3, PTD4, PTD5, PTD6, PTD7},
+    gpioe::{self, PTE0, PTE1},
+    Alternate, GpioExt, Output, PushPull, ALT0, ALT7,
+};
+use mcg::{ClockMode, Fbe, MultipurposeClockGenerator, Pbe, Pee};
+use osc::Oscillator;
+use sim::{Clocks, SystemIntegrationModule};
+
+/// Frequency of the board crystal, in MHz
+pub const CRYSTAL_FREQUENCY: u32 = 16;
+
+/// Frequency of the PLL, which drives the MCG output clock, in MHz
+pub const PLL_FREQUENCY: u32 = 72;
+
+/// Load capacitance of the board crystal, in pF
+pub const CRYSTAL_CAPACITANCE: u8 = 10;
+
+/// Core clock divider, from the 72 MHz PLL
+pub const CORE_DIVIDER: u8 = 1;
+
+/// Bus clock divider, from the 72 MHz PLL
+pub const BUS_DIVIDER: u8 = 2;
+
+/// Flash clock divider, from the 72 MHz PLL
+pub const FLASH_DIVIDER: u8 = 3;
+
+/// On-board LED, on pin 13
+pub type Led = PTC5<Output<PushPull>>;
+
+/// Returns the frequency of the board crystal, for `MultipurposeClockGenerator::new`
+pub fn crystal_frequency() -> MegaHertz<u32> {
+    CRYSTAL_FREQUENCY.mhz()
+}
+
+/// Configures the clocks the way the Teensy bootloader leaves them: the 16 MHz crystal drives the
+/// PLL at 72 MHz, for a 72 MHz core, 36 MHz bus and 24 MHz flash clock
+///
+/// The MCG is only switched to the PLL from its reset mode (FEI); in any other mode it is left as
+/// is. In both cases the clocks are derived from the MCG output frequency.
+pub fn configure_clocks(
+    osc: &Oscillator,
+    sim: &mut SystemIntegrationModule,
+    mcg: &mut MultipurposeClockGenerator,
+) -> Clocks {
+    osc.set_capacitance(CRYSTAL_CAPACITANCE);
+    sim.set_dividers(CORE_DIVIDER, BUS_DIVIDER, FLASH_DIVIDER);
+
+    if let ClockMode::Fei(fei) = mcg.clock_mode() {
+        let fbe: Fbe = fei.into();
+        let pbe: Pbe = fbe.into();
+        let _pee: Pee = pbe.into();
+    }
+
+    sim.get_clocks(mcg.get_output_frequency())
+}
+
+macro_rules! port {
+    ($Port:ident, $gpiox:ident, $doc:expr, [$($ptxi:ident: $PTXi:ident<$MODE:ty>,)*]) => {
+        #[doc = "Parts of port "]
+        #[doc = $doc]
+        #[doc = " not mapped to a board pin"]
+        pub struct $Port {
+            /// Port peripherals, given back by `Pins::free`
+            pub peripherals: $gpiox::Peripherals,
+
+            /// Digital Filter Clock Register
+            pub dfcr: $gpiox::DFCR,
+
+            /// Digital Filter Enable Register
+            pub dfer: $gpiox::DFER,
+
+            /// Digital Filter Width Register
+            pub dfwr: $gpiox::DFWR,
+
+            /// Global Pin Control High Register
+            pub gpchr: $gpiox::GPCHR,
+
+            /// Global Pin Control Low Register
+            pub gpclr: $gpiox::GPCLR,
+
+            /// Interrupt Status Flag Register
+            pub isfr: $gpiox::ISFR,
+
+            /// Pin Control Register n
+            pub pcr: $gpiox::PCR,
+
+            /// Port Clear Output Register
+            pub pcor: $gpiox::PCOR,
+
+            /// Port Data Direction Register
+            pub pddr: $gpiox::PDDR,
+
+            /// Port Data Input Register
+            pub pdir: $gpiox::PDIR,
+
+            /// Port Data Output Register
+            pub pdor: $gpiox::PDOR,
+
+            /// Port Set Output Register
+            pub psor: $gpiox::PSOR,
+
+            /// Port Toggle Output Register
+            pub ptor: $gpiox::PTOR,
+
+            $(
+                /// Pin not broken out on the board
+                pub $ptxi: $PTXi<$MODE>,
+            )*
+        }
+    };
+}
+
+// Takes the parts of a split port that are left once its board pins are moved out
+macro_rules! leftovers {
+    ($Port:ident, $parts:ident, [$($ptxi:ident),*]) => {
+        $Port {
+            peripherals: $parts.peripherals,
+            dfcr: $parts.dfcr,
+            dfer: $parts.dfer,
+            dfwr: $parts.dfwr,
+            gpchr: $parts.gpchr,
+            gpclr: $parts.gpclr,
+            isfr: $parts.isfr,
+            pcr: $parts.pcr,
+            pcor: $parts.pcor,
+            pddr: $parts.pddr,
+            pdir: $parts.pdir,
+            pdor: $parts.pdor,
+            psor: $parts.psor,
+            ptor: $parts.ptor,
+            $($ptxi: $parts.$ptxi,)*
+        }
+    };
+}
+
+port!(PortA, gpioa, "A", [
+    pta0: PTA0<Alternate<ALT7>>,
+    pta1: PTA1<Alternate<ALT7>>,
+    pta2: PTA2<Alternate<ALT7>>,
+    pta3: PTA3<Alternate<ALT7>>,
+    pta18: PTA18<Alternate<ALT0>>,
+    pta19: PTA19<Alternate<ALT0>>,
+]);
+port!(PortB, gpiob, "B", []);
+port!(PortC, gpioc, "C", []);
+port!(PortD, gpiod, "D", []);
+port!(PortE, gpioe, "E", []);
+
+/// Port peripherals given back by `Pins::free`
+pub type Ports = ((PTA, PORTA), (PTB, PORTB), (PTC, PORTC), (PTD, PORTD), (PTE, PORTE));
+
+/// Pins of the board, by silkscreen number
+pub struct Pins {
+    /// Pin 0, RX1
+    pub p0: PTB16<Alternate<ALT0>>,
+
+    /// Pin 1, TX1
+    pub p1: PTB17<Alternate<ALT0>>,
+
+    /// Pin 2
+    pub p2: PTD0<Alternate<ALT0>>,
+
+    /// Pin 3, CAN TX
+    pub p3: PTA12<Alternate<ALT0>>,
+
+    /// Pin 4, CAN RX
+    pub p4: PTA13<Alternate<ALT0>>,
+
+    /// Pin 5
+    pub p5: PTD7<Alternate<ALT0>>,
+
+    /// Pin 6
+    pub p6: PTD4<Alternate<ALT0>>,
+
+    /// Pin 7, RX3
+    pub p7: PTD2<Alternate<ALT0>>,
+
+    /// Pin 8, TX3
+    pub p8: PTD3<Alternate<ALT0>>,
+
+    /// Pin 9, RX2
+    pub p9: PTC3<Alternate<ALT0>>,
+
+    /// Pin 10, TX2
+    pub p10: PTC4<Alternate<ALT0>>,
+
+    /// Pin 11, DOUT
+    pub p11: PTC6<Alternate<ALT0>>,
+
+    /// Pin 12, DIN
+    pub p12: PTC7<Alternate<ALT0>>,
+
+    /// Pin 13, SCK and LED
+    pub p13: PTC5<Alternate<ALT0>>,
+
+    /// Pin 14, A0
+    pub p14: PTD1<Alternate<ALT0>>,
+
+    /// Pin 15, A1
+    pub p15: PTC0<Alternate<ALT0>>,
+
+    /// Pin 16, A2
+    pub p16: PTB0<Alternate<ALT0>>,
+
+    /// Pin 17, A3
+    pub p17: PTB1<Alternate<ALT0>>,
+
+    /// Pin 18, A4 and SDA0
+    pub p18: PTB3<Alternate<ALT0>>,
+
+    /// Pin 19, A5 and SCL0
+    pub p19: PTB2<Alternate<ALT0>>,
+
+    /// Pin 20, A6
+    pub p20: PTD5<Alternate<ALT0>>,
+
+    /// Pin 21, A7
+    pub p21: PTD6<Alternate<ALT0>>,
+
+    /// Pin 22, A8
+    pub p22: PTC1<Alternate<ALT0>>,
+
+    /// Pin 23, A9
+    pub p23: PTC2<Alternate<ALT0>>,
+
+    /// Pin 24, bottom pad
+    pub p24: PTA5<Alternate<ALT0>>,
+
+    /// Pin 25, bottom pad
+    pub p25: PTB19<Alternate<ALT0>>,
+
+    /// Pin 26, bottom pad
+    pub p26: PTE1<Alternate<ALT0>>,
+
+    /// Pin 27, bottom pad
+    pub p27: PTC9<Alternate<ALT0>>,
+
+    /// Pin 28, bottom pad
+    pub p28: PTC8<Alternate<ALT0>>,
+
+    /// Pin 29, bottom pad
+    pub p29: PTC10<Alternate<ALT0>>,
+
+    /// Pin 30, bottom pad
+    pub p30: PTC11<Alternate<ALT0>>,
+
+    /// Pin 31, bottom pad
+    pub p31: PTE0<Alternate<ALT0>>,
+
+    /// Pin 32, bottom pad
+    pub p32: PTB18<Alternate<ALT0>>,
+
+    /// Pin 33, bottom pad
+    pub p33: PTA4<Alternate<ALT7>>,
+
+    /// Port A registers, crystal and SWD pins
+    pub porta: PortA,
+
+    /// Port B registers
+    pub portb: PortB,
+
+    /// Port C registers
+    pub portc: PortC,
+
+    /// Port D registers
+    pub portd: PortD,
+
+    /// Port E registers
+    pub porte: PortE,
+}
+
+impl Pins {
+    /// Splits every port and maps its pins to the board pins
+    ///
+    /// The crystal (PTA18, PTA19) and the SWD pins used by the bootloader (PTA0 to PTA3) are not
+    /// broken out: they stay in their reset state in `Pins::porta`, with the rest of the port
+    /// registers.
+    pub fn new(
+        porta: (PTA, PORTA),
+        portb: (PTB, PORTB),
+        portc: (PTC, PORTC),
+        portd: (PTD, PORTD),
+        porte: (PTE, PORTE),
+        scgc5: &SCGC5,
+    ) -> Pins {
+        let a = porta.split(scgc5);
+        let b = portb.split(scgc5);
+        let c = portc.split(scgc5);
+        let d = portd.split(scgc5);
+        let e = porte.split(scgc5);
+
+        Pins {
+            p0: b.ptb16,
+            p1: b.ptb17,
+            p2: d.ptd0,
+            p3: a.pta12,
+            p4: a.pta13,
+            p5: d.ptd7,
+            p6: d.ptd4,
+            p7: d.ptd2,
+            p8: d.ptd3,
+            p9: c.ptc3,
+            p10: c.ptc4,
+            p11: c.ptc6,
+            p12: c.ptc7,
+            p13: c.ptc5,
+            p14: d.ptd1,
+            p15: c.ptc0,
+            p16: b.ptb0,
+            p17: b.ptb1,
+            p18: b.ptb3,
+            p19: b.ptb2,
+            p20: d.ptd5,
+            p21: d.ptd6,
+            p22: c.ptc1,
+            p23: c.ptc2,
+            p24: a.pta5,
+            p25: b.ptb19,
+            p26: e.pte1,
+            p27: c.ptc9,
+            p28: c.ptc8,
+            p29: c.ptc10,
+            p30: c.ptc11,
+            p31: e.pte0,
+            p32: b.ptb18,
+            p33: a.pta4,
+            porta: leftovers!(PortA, a, [pta0, pta1, pta2, pta3, pta18, pta19]),
+            portb: leftovers!(PortB, b, []),
+            portc: leftovers!(PortC, c, []),
+            portd: leftovers!(PortD, d, []),
+            porte: leftovers!(PortE, e, []),
+        }
+    }
+
+    /// Releases the port peripherals
+    ///
+    /// Every pin has to be given back in its reset state, see `into_reset_state`.
+    pub fn free(self) -> Ports {
+        (
+            self.porta.peripherals.free(),
+            self.portb.peripherals.free(),
+            self.portc.peripherals.free(),
+            self.portd.peripherals.free(),
+            self.porte.peripherals.free(),
+        )
+    }
+}